@@ -1,4 +1,5 @@
-use crate::core::sprite::Sprite;
+use flesh::sprite::Sprite;
+use flesh::time::FrameTime;
 use macroquad::prelude::*;
 
 pub enum CollectableType {
@@ -8,6 +9,8 @@ pub enum CollectableType {
 pub struct Collctable {
     pub radius: f32,
     pub position: Vec2,
+    // Position from the last update. Used for drawing between fixed updates.
+    previous_position: Vec2,
     pub collectable_type: CollectableType,
     collected_tick: u128,
     pub collected: bool,
@@ -17,7 +20,8 @@ impl Collctable {
     pub fn water(value: i32) -> Self {
         Self {
             radius: 32.0,
-            position: Vec2::zero(),
+            position: Vec2::ZERO,
+            previous_position: Vec2::ZERO,
             collectable_type: CollectableType::Water(value),
             collected_tick: 0,
             collected: false,
@@ -26,10 +30,11 @@ impl Collctable {
 }
 
 impl Collctable {
-    pub fn update(&mut self, speed: f32, player_position: Vec2) {
+    pub fn update(&mut self, time: &FrameTime, speed: f32, player_position: Vec2) {
+        self.previous_position = self.position;
         let distance = self.position.distance_squared(player_position);
         if self.collected_tick > 0 {
-            self.collected_tick += crate::ONE_FRAME.as_millis();
+            self.collected_tick += time.delta.as_millis();
 
             let percentage = self.collected_tick as f32 / 500.0;
             self.position = self.position.lerp(player_position, percentage);
@@ -47,8 +52,15 @@ impl Collctable {
         }
     }
 
-    pub fn draw(&self) {
-        draw_circle(self.position.x, self.position.y, self.radius, GREEN);
+    pub fn draw(&self, alpha: f32) {
+        let position = self.previous_position.lerp(self.position, alpha);
+        draw_circle(position.x, position.y, self.radius, GREEN);
+    }
+
+    /// Move without blending from the old position on the next draw.
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.previous_position = position;
     }
 
     pub fn closeby(&mut self) {
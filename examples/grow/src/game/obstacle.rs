@@ -1,10 +1,13 @@
-use crate::core::sprite::Sprite;
+use flesh::sprite::Sprite;
+use flesh::time::FrameTime;
 use macroquad::prelude::*;
 
 pub struct Obstacle {
     sprite: Sprite,
     radius: f32,
     pub position: Vec2,
+    // Position from the last update. Used for drawing between fixed updates.
+    previous_position: Vec2,
     velocity: Vec2,
     hit_cooldown: i32,
 }
@@ -15,14 +18,16 @@ impl Obstacle {
             sprite: Sprite::new(),
             radius: radius,
             position: position,
-            velocity: Vec2::zero(),
+            previous_position: position,
+            velocity: Vec2::ZERO,
             hit_cooldown: 0,
         }
     }
 
-    pub fn update(&mut self, speed: f32) {
-        self.sprite.update();
+    pub fn update(&mut self, time: &FrameTime, speed: f32) {
+        self.sprite.update(time);
 
+        self.previous_position = self.position;
         self.position += self.velocity;
         self.position.y += speed;
 
@@ -31,10 +36,11 @@ impl Obstacle {
         }
     }
 
-    pub fn draw(&self) {
-        self.sprite.draw(self.position, 0.0);
+    pub fn draw(&self, alpha: f32) {
+        let position = self.previous_position.lerp(self.position, alpha);
+        self.sprite.draw(position, 0.0);
 
-        draw_circle(self.position.x, self.position.y, self.radius, RED);
+        draw_circle(position.x, position.y, self.radius, RED);
     }
 
     pub fn is_hit(&self, other: Vec2, other_radius: f32) -> bool {
@@ -1,10 +1,14 @@
-use crate::core::sprite::Sprite;
+use flesh::input;
+use flesh::sprite::Sprite;
+use flesh::time::FrameTime;
 use macroquad::prelude::*;
 
 pub struct Player {
     sprite: Sprite,
     pub position: Vec2,
     pub rotation: f32,
+    // Position from the last update. Used for drawing between fixed updates.
+    previous_position: Vec2,
 
     // iFrame
     iframe: i32,
@@ -20,7 +24,7 @@ pub struct Player {
 impl Player {
     pub fn new() -> Self {
         let mut sprite = Sprite::new();
-        let animation_manager = crate::ANIMATION_MANAGER.lock().unwrap();
+        let animation_manager = flesh::ANIMATION_MANAGER.lock().unwrap();
         if let Some(anim) = animation_manager.get("player-idle") {
             sprite.play(anim);
         }
@@ -29,6 +33,7 @@ impl Player {
             sprite: sprite,
             position: Vec2::new(0.0, 0.0),
             rotation: 0.0,
+            previous_position: Vec2::new(0.0, 0.0),
 
             iframe: 0,
             direction: 1,
@@ -38,10 +43,11 @@ impl Player {
         }
     }
 
-    pub fn update(&mut self) {
-        self.sprite.update();
+    pub fn update(&mut self, time: &FrameTime) {
+        self.sprite.update(time);
+        self.previous_position = self.position;
 
-        if is_key_pressed(KeyCode::Space) {
+        if input::is_key_pressed(KeyCode::Space) {
             self.switching_direction();
         }
 
@@ -66,21 +72,28 @@ impl Player {
             self.external_force = self.external_force.min(0);
         }
 
-        self.water -= crate::ONE_FRAME.as_millis() as i32;
+        self.water -= time.delta.as_millis() as i32;
         if self.iframe > 0 {
             self.iframe -= 1;
         }
     }
 
-    pub fn draw(&self) {
-        // self.sprite.draw(self.position, self.rotation);
+    pub fn draw(&self, alpha: f32) {
+        let position = self.previous_position.lerp(self.position, alpha);
+        // self.sprite.draw(position, self.rotation);
         if self.water > 0 {
-            draw_circle(self.position.x, self.position.y, 15.0, BLACK);
+            draw_circle(position.x, position.y, 15.0, BLACK);
         } else {
-            draw_circle_lines(self.position.x, self.position.y, 15.0, 2.0, BLACK);
+            draw_circle_lines(position.x, position.y, 15.0, 2.0, BLACK);
         }
     }
 
+    /// Move without blending from the old position on the next draw.
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.previous_position = position;
+    }
+
     pub fn bouncing(&mut self, next_x: f32) {
         if next_x >= crate::GAME_WIDTH {
             self.bouncing_force = -rand::RandomRange::gen_range(10, 15);
@@ -0,0 +1,37 @@
+#![windows_subsystem = "windows"]
+pub mod game;
+pub mod scenes;
+
+use flesh::app::App;
+use flesh::loading_scene::AssetJob;
+use flesh::scene::GameScene;
+
+pub const LIMIT_FPS: u64 = 60;
+
+pub const GAME_WIDTH: f32 = 300.0;
+pub const GAME_HEIGHT: f32 = 800.0;
+
+#[cfg(feature = "embedded-assets")]
+static RESOURCES: include_dir::Dir = include_dir::include_dir!("$CARGO_MANIFEST_DIR/resources");
+
+fn main() {
+    let app = App::new("Grow")
+        .virtual_size(crate::GAME_WIDTH as u32, crate::GAME_HEIGHT as u32)
+        .tick_rate(crate::LIMIT_FPS)
+        .high_dpi(true)
+        .resizable(false)
+        .loading(vec![AssetJob::manifest("./resources/manifest.json")])
+        .initial_scene(|| {
+            let mut title_scene = scenes::title::TitleScene::new();
+            title_scene.init();
+            Box::new(title_scene)
+        });
+
+    #[cfg(feature = "hot-reload")]
+    let app = app.hot_reload("./resources");
+
+    #[cfg(feature = "embedded-assets")]
+    let app = app.embedded_assets("./resources", &RESOURCES);
+
+    app.run();
+}
@@ -1,4 +1,5 @@
-use crate::core::scene::{GameScene, SceneTransition};
+use flesh::scene::{GameScene, SceneError, SceneTransition};
+use flesh::time::FrameTime;
 use lerp::Lerp;
 use macroquad::prelude::*;
 
@@ -15,8 +16,8 @@ impl EndingScene {
 impl GameScene for EndingScene {
     fn init(&mut self) {}
 
-    fn update(&mut self) -> Result<SceneTransition, i32> {
-        self.tick += crate::ONE_FRAME.as_millis();
+    fn update(&mut self, time: &FrameTime) -> Result<SceneTransition, SceneError> {
+        self.tick += time.delta.as_millis();
         if self.tick > 1000 {
             let mut title_scene = crate::scenes::title::TitleScene::new();
             title_scene.init();
@@ -26,7 +27,7 @@ impl GameScene for EndingScene {
         Ok(SceneTransition::None)
     }
 
-    fn draw(&mut self) {
+    fn draw(&mut self, _alpha: f32) {
         clear_background(BLACK);
     }
 }
@@ -1,8 +1,12 @@
 use lerp::Lerp;
 use macroquad::prelude::*;
 use std::collections::VecDeque;
+use std::time::Duration;
 
-use crate::core::scene::{GameScene, SceneTransition};
+use flesh::input;
+use flesh::scene::{GameScene, SceneError, SceneTransition};
+use flesh::time::FrameTime;
+use flesh::transition::Transition;
 
 use crate::game::collectable::{Collctable, CollectableType};
 use crate::game::obstacle::Obstacle;
@@ -27,6 +31,8 @@ pub struct GameplayScene {
     items: VecDeque<Collctable>,
     speed: f32,
     spawn_cooldown: i32,
+    // Waiting for the player to answer "Are you sure?"
+    confirm_quit: bool,
 }
 
 impl GameplayScene {
@@ -42,18 +48,32 @@ impl GameplayScene {
             items: VecDeque::new(),
             speed: 0.0,
             spawn_cooldown: 0,
+            confirm_quit: false,
         }
     }
 }
 
 impl GameScene for GameplayScene {
     fn init(&mut self) {
-        self.player.position = Vec2::new(screen_width() / 2.0, screen_height() - 100.0);
+        self.player.set_position(Vec2::new(
+            crate::GAME_WIDTH / 2.0,
+            crate::GAME_HEIGHT - 100.0,
+        ));
     }
 
-    fn update(&mut self) -> Result<SceneTransition, i32> {
+    fn update(&mut self, time: &FrameTime) -> Result<SceneTransition, SceneError> {
+        if self.confirm_quit {
+            if input::is_key_pressed(KeyCode::Y) {
+                return Ok(SceneTransition::Quit);
+            } else if input::is_key_pressed(KeyCode::N) {
+                self.confirm_quit = false;
+            }
+
+            return Ok(SceneTransition::None);
+        }
+
         match self.state {
-            GameplayState::Start => match self.tick.checked_sub(crate::ONE_FRAME.as_millis()) {
+            GameplayState::Start => match self.tick.checked_sub(time.delta.as_millis()) {
                 Some(v) => {
                     self.tick = v;
                 }
@@ -62,12 +82,12 @@ impl GameScene for GameplayScene {
                 }
             },
             GameplayState::Playing => {
-                return self.update_playing();
+                return self.update_playing(time);
             }
             GameplayState::GameOver => {
-                self.tick += crate::ONE_FRAME.as_millis();
+                self.tick += time.delta.as_millis();
 
-                if is_key_down(KeyCode::Space) && self.tick > 300 {
+                if input::is_key_down(KeyCode::Space) && self.tick > 300 {
                     let mut scene = GameplayScene::new();
                     scene.init();
                     return Ok(SceneTransition::Replace(Box::new(scene)));
@@ -78,50 +98,73 @@ impl GameScene for GameplayScene {
         Ok(SceneTransition::None)
     }
 
-    fn draw(&mut self) {
+    fn draw(&mut self, alpha: f32) {
         clear_background(WHITE);
 
         for obstacle in self.obstacles.iter() {
-            obstacle.draw();
+            obstacle.draw(alpha);
         }
 
         for item in self.items.iter_mut() {
-            item.draw();
+            item.draw(alpha);
         }
 
-        self.player.draw();
+        self.player.draw(alpha);
 
         self.draw_ui();
 
         if matches!(self.state, GameplayState::Start) {
             let seconds = (self.tick as f32 / 1000.0).ceil() as u128;
-            crate::core::utils::draw_text_center(
+            flesh::utils::draw_text_center(
                 format!("{}", seconds).as_str(),
                 96,
                 crate::GAME_HEIGHT / 2.0,
             );
         } else if matches!(self.state, GameplayState::GameOver) {
-            crate::core::utils::draw_text_center("GAME OVER", 72, crate::GAME_HEIGHT / 2.0);
-            crate::core::utils::draw_text_center("`Space` for retry", 32, crate::GAME_HEIGHT * 0.6);
+            flesh::utils::draw_text_center("GAME OVER", 72, crate::GAME_HEIGHT / 2.0);
+            flesh::utils::draw_text_center("`Space` for retry", 32, crate::GAME_HEIGHT * 0.6);
+        }
+
+        if self.confirm_quit {
+            flesh::utils::draw_text_center("Are you sure?", 48, crate::GAME_HEIGHT * 0.4);
+            flesh::utils::draw_text_center(
+                "`Y` quit / `N` continue",
+                24,
+                crate::GAME_HEIGHT * 0.45,
+            );
         }
     }
+
+    fn on_quit_requested(&mut self) -> bool {
+        // Quit right away after game over or when the quit key is pressed again.
+        if matches!(self.state, GameplayState::GameOver) || self.confirm_quit {
+            return true;
+        }
+
+        self.confirm_quit = true;
+        false
+    }
 }
 
 impl GameplayScene {
-    fn update_playing(&mut self) -> Result<SceneTransition, i32> {
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    fn update_playing(&mut self, time: &FrameTime) -> Result<SceneTransition, SceneError> {
         let speed = self.speed;
         if rand::RandomRange::gen_range(0, 20) as i32 == 0 && self.spawn_cooldown <= 0 {
             self.spawn_cooldown = self.spawn_obstacle() * 2 / (self.speed as i32).max(1);
         }
 
-        self.spawn_item();
+        self.spawn_item(time);
 
         if self.spawn_cooldown > 0 {
             self.spawn_cooldown -= 1;
         }
 
         for obstacle in self.obstacles.iter_mut() {
-            obstacle.update(speed);
+            obstacle.update(time, speed);
             if self.player.is_invincible() == false && obstacle.is_hit(self.player.position, 8.0) {
                 obstacle.hit();
 
@@ -147,7 +190,7 @@ impl GameplayScene {
 
         for index in 0..self.items.len() {
             let mut item = self.items.get_mut(index).unwrap();
-            item.update(speed, self.player.position);
+            item.update(time, speed, self.player.position);
 
             if item.collected == true {
                 if let CollectableType::Water(value) = item.collectable_type {
@@ -162,7 +205,7 @@ impl GameplayScene {
             self.items.remove(*index);
         }
 
-        self.player.update();
+        self.player.update(time);
 
         self.speed += 0.4;
         self.speed = self.speed.min(MAX_SPEED);
@@ -176,7 +219,10 @@ impl GameplayScene {
             let mut scene = crate::scenes::ending::EndingScene::new();
             scene.init();
 
-            return Ok(SceneTransition::Replace(Box::new(scene)));
+            return Ok(SceneTransition::ReplaceWith(
+                Box::new(scene),
+                Transition::fade(BLACK, Duration::from_millis(1000)),
+            ));
         }
 
         Ok(SceneTransition::None)
@@ -193,8 +239,8 @@ impl GameplayScene {
         radius
     }
 
-    fn spawn_item(&mut self) {
-        match self.item_tick.checked_sub(crate::ONE_FRAME.as_millis()) {
+    fn spawn_item(&mut self, time: &FrameTime) {
+        match self.item_tick.checked_sub(time.delta.as_millis()) {
             Some(v) => {
                 self.item_tick = v;
             }
@@ -204,7 +250,7 @@ impl GameplayScene {
                     rand::RandomRange::gen_range(10 + 32, crate::GAME_WIDTH as i32 - (10 + 32));
 
                 let mut item = Collctable::water(5000);
-                item.position = Vec2::new(pos_x as f32, 10.0 - item.radius);
+                item.set_position(Vec2::new(pos_x as f32, 10.0 - item.radius));
                 self.items.push_back(item);
             }
         }
@@ -214,7 +260,7 @@ impl GameplayScene {
 impl GameplayScene {
     fn draw_ui(&self) {
         let metre = self.surface_distance.ceil() as i32;
-        crate::core::utils::draw_text_center(format!("{} cm", metre).as_str(), 72, 100.0);
+        flesh::utils::draw_text_center(format!("{} cm", metre).as_str(), 72, 100.0);
 
         let percentage = self.player.water as f32 / 5000.0;
         let width = crate::GAME_WIDTH - 20.0;
@@ -237,3 +283,28 @@ impl GameplayScene {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flesh::headless::HeadlessRunner;
+
+    #[test]
+    fn player_loses_water_after_the_countdown() {
+        let mut runner = HeadlessRunner::new(crate::LIMIT_FPS);
+        let mut scene = GameplayScene::new();
+        scene.init();
+        runner.push(Box::new(scene));
+
+        // The 3 second countdown doesn't use water
+        runner.step(180);
+        let scene = runner.scene_manager().top::<GameplayScene>().unwrap();
+        assert_eq!(scene.player().water, 5000);
+
+        // 16 ms per tick and macroquad's default rand seed, so the spawns and pickups are the same every run.
+        // The player picks up water on the way, so it is more than 5000 minus the playing time.
+        runner.step(420);
+        let scene = runner.scene_manager().top::<GameplayScene>().unwrap();
+        assert_eq!(scene.player().water, 3688);
+    }
+}
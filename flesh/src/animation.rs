@@ -0,0 +1,292 @@
+use crate::handle::{Assets, Handle};
+use crate::textures::{TextureError, TextureRef};
+use macroquad::math::Rect;
+use macroquad::texture::Texture2D;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Frame length of animations that don't set one.
+pub const DEFAULT_FRAME_LENGTH: Duration = Duration::from_millis(1000 / 18);
+
+/// How `Sprite` goes through the frames.
+/// In the manifest: "forward", "reverse", "ping_pong", "hold_last" or { "times": 3 }.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackMode {
+    // First to last frame. It loops when the sprite loops.
+    #[default]
+    Forward,
+    // Last to first frame. It loops when the sprite loops.
+    Reverse,
+    // First to last, then back to the first frame without repeating the last one.
+    PingPong,
+    // Forward N times, then stop on the last frame
+    Times(u32),
+    // Forward once, then stop on the last frame even when the sprite loops
+    HoldLast,
+}
+
+/// Named event on a frame, e.g. "footstep" on frame 3. `Sprite` sends it when it enters the frame.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct FrameEvent {
+    pub frame: usize,
+    pub name: String,
+}
+
+/// One image of the animation: the whole texture or the `source` rect of a sprite sheet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub texture: Handle<Texture2D>,
+    pub source: Option<Rect>,
+    // Use `Animation::frame_length` when it is not set
+    pub duration: Option<Duration>,
+}
+
+impl Frame {
+    pub fn new(texture: Handle<Texture2D>) -> Self {
+        Self {
+            texture,
+            source: None,
+            duration: None,
+        }
+    }
+
+    pub fn with_source(texture: Handle<Texture2D>, source: Rect) -> Self {
+        Self {
+            texture,
+            source: Some(source),
+            duration: None,
+        }
+    }
+}
+
+/// Contain `Frame`s and use it for playing animation.
+/// The frame texture handle will link to texture on crate::TEXTURE_MANAGER
+/// The Animation is member of `Sprite`.
+pub struct Animation {
+    // Length of the frames without their own duration
+    pub frame_length: Duration,
+    pub mode: PlaybackMode,
+    pub frames: Vec<Frame>,
+    pub events: Vec<FrameEvent>,
+    pub name: String,
+    // Keep the frame textures loaded while the animation (or a `Sprite` playing it) is alive
+    refs: Vec<TextureRef>,
+    // Bumped by `AnimationManager::insert()` when it replace the animation with the same name
+    revision: u32,
+}
+
+impl Clone for Animation {
+    fn clone(&self) -> Animation {
+        Animation {
+            frame_length: self.frame_length,
+            mode: self.mode,
+            frames: self.frames.clone(),
+            events: self.events.clone(),
+            name: self.name.clone(),
+            refs: self.refs.clone(),
+            revision: self.revision,
+        }
+    }
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation {
+    pub fn new() -> Self {
+        Self {
+            frame_length: DEFAULT_FRAME_LENGTH,
+            mode: PlaybackMode::Forward,
+            frames: vec![],
+            events: vec![],
+            name: "".to_owned(),
+            refs: vec![],
+            revision: 0,
+        }
+    }
+
+    /// Frame names can be texture names or sprite sheet region names.
+    /// A frame name that is not found is reported as `TextureError::UnknownName` and drawn with the placeholder.
+    /// It fails with the error in strict mode.
+    pub fn from_frames(name: &str, frame_names: Vec<&str>) -> Result<Self, TextureError> {
+        let mut frames = vec![];
+        let mut refs = vec![];
+        let mut texture_manager = crate::TEXTURE_MANAGER.lock().unwrap();
+        for name in frame_names.iter() {
+            match texture_manager.get_frame(name) {
+                Some(frame) => {
+                    frames.push(frame);
+                    refs.extend(texture_manager.acquire(frame.texture));
+                }
+                None => {
+                    let error = TextureError::UnknownName((*name).to_owned());
+                    texture_manager.report(error.clone());
+                    if texture_manager.is_strict() {
+                        return Err(error);
+                    }
+                    frames.push(Frame::new(Handle::dangling()));
+                }
+            }
+        }
+
+        Ok(Self {
+            frame_length: DEFAULT_FRAME_LENGTH,
+            mode: PlaybackMode::Forward,
+            frames: frames,
+            events: vec![],
+            name: name.to_owned(),
+            refs,
+            revision: 0,
+        })
+    }
+
+    pub fn with_frame_length(mut self, frame_length: Duration) -> Self {
+        self.frame_length = frame_length;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: PlaybackMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_event(mut self, frame: usize, name: &str) -> Self {
+        self.events.push(FrameEvent {
+            frame,
+            name: name.to_owned(),
+        });
+        self
+    }
+
+    pub fn events_at(&self, frame: usize) -> impl Iterator<Item = &FrameEvent> {
+        self.events.iter().filter(move |v| v.frame == frame)
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    /// How long the frame at `index` is shown.
+    pub fn frame_length_at(&self, index: usize) -> Duration {
+        match self.frames.get(index) {
+            Some(Frame {
+                duration: Some(duration),
+                ..
+            }) => *duration,
+            _ => self.frame_length,
+        }
+    }
+
+    pub fn total_length(&self) -> Duration {
+        (0..self.frames.len())
+            .map(|i| self.frame_length_at(i))
+            .sum()
+    }
+}
+
+pub struct AnimationManager {
+    animations: Assets<Animation>,
+    names: HashMap<String, Handle<Animation>>,
+}
+
+impl Default for AnimationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnimationManager {
+    pub fn new() -> Self {
+        Self {
+            animations: Assets::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    pub fn add(
+        &mut self,
+        name: &str,
+        frame_names: Vec<&str>,
+    ) -> Result<Handle<Animation>, TextureError> {
+        match Animation::from_frames(name, frame_names) {
+            Ok(animation) => {
+                let handle = self.insert(animation);
+                println!("AnimationManger: add(): success: {}", name);
+                Ok(handle)
+            }
+            Err(e) => {
+                println!("AnimationManger: add(): failed: {}: {}", name, e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Add an animation that is already created.
+    /// It replace the animation with the same name and keep its handle.
+    pub fn insert(&mut self, mut animation: Animation) -> Handle<Animation> {
+        if let Some(handle) = self.names.get(&animation.name).copied() {
+            if let Some(old) = self.animations.get_mut(handle) {
+                animation.revision = old.revision.wrapping_add(1);
+                *old = animation;
+                return handle;
+            }
+        }
+
+        let name = animation.name.clone();
+        let handle = self.animations.insert(animation);
+        self.names.insert(name, handle);
+        handle
+    }
+
+    pub fn remove(&mut self, handle: Handle<Animation>) -> Option<Animation> {
+        let animation = self.animations.remove(handle)?;
+        self.names.remove(&animation.name);
+        Some(animation)
+    }
+
+    pub fn unload(&mut self, name: &str) -> bool {
+        match self.get_handle(name) {
+            Some(handle) => self.remove(handle).is_some(),
+            None => false,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Animation> {
+        match self.names.get(name) {
+            Some(handle) => self.animations.get(*handle),
+            None => None,
+        }
+    }
+
+    pub fn get_by_handle(&self, handle: Handle<Animation>) -> Option<&Animation> {
+        self.animations.get(handle)
+    }
+
+    pub fn get_handle(&self, name: &str) -> Option<Handle<Animation>> {
+        self.names.get(name).copied()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.names.keys()
+    }
+}
+
+/// Frame length of `test_animation()`.
+#[cfg(test)]
+pub(crate) const TEST_FRAME_LENGTH: Duration = Duration::from_millis(100);
+
+/// Animation with `frame_count` frames of an empty texture. Tests can play it without a window.
+#[cfg(test)]
+pub(crate) fn test_animation(name: &str, frame_count: usize) -> Animation {
+    let mut textures = Assets::new();
+    let texture = textures.insert(Texture2D::empty());
+    let mut animation = Animation::new().with_frame_length(TEST_FRAME_LENGTH);
+    animation.name = name.to_owned();
+    animation.frames = vec![Frame::new(texture); frame_count];
+    animation
+}
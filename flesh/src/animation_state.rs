@@ -0,0 +1,329 @@
+use crate::animation::Animation;
+use crate::sprite::{Sprite, SpriteEvent};
+use crate::time::FrameTime;
+use macroquad::math::Vec2;
+use std::collections::HashMap;
+
+/// What a `StateTransition` checks. Every condition of a transition must hold.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    // The bool parameter has this value
+    Bool { name: String, value: bool },
+    // The float parameter is greater than the value
+    Greater { name: String, value: f32 },
+    // The float parameter is less than the value
+    Less { name: String, value: f32 },
+    // The current animation finished a cycle during this update
+    AnimationEnd,
+}
+
+/// Move from one state to another when the conditions hold.
+///
+/// ```ignore
+/// StateTransition::new("idle", "run").when_float_greater("speed", 0.1)
+/// StateTransition::any("hit").when_bool("hurt", true)
+/// StateTransition::new("attack", "idle").on_end()
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateTransition {
+    // `None` is any state
+    pub from: Option<String>,
+    pub to: String,
+    pub conditions: Vec<Condition>,
+    // Let the current animation finish its cycle before changing
+    pub wait_for_end: bool,
+    // Start the new animation at the same normalized time, e.g. walk -> run on the same foot
+    pub keep_normalized_time: bool,
+}
+
+impl StateTransition {
+    pub fn new(from: &str, to: &str) -> Self {
+        Self {
+            from: Some(from.to_owned()),
+            to: to.to_owned(),
+            conditions: vec![],
+            wait_for_end: false,
+            keep_normalized_time: false,
+        }
+    }
+
+    /// Transition from every state but `to`.
+    pub fn any(to: &str) -> Self {
+        Self {
+            from: None,
+            ..Self::new("", to)
+        }
+    }
+
+    pub fn when_bool(mut self, name: &str, value: bool) -> Self {
+        self.conditions.push(Condition::Bool {
+            name: name.to_owned(),
+            value,
+        });
+        self
+    }
+
+    pub fn when_float_greater(mut self, name: &str, value: f32) -> Self {
+        self.conditions.push(Condition::Greater {
+            name: name.to_owned(),
+            value,
+        });
+        self
+    }
+
+    pub fn when_float_less(mut self, name: &str, value: f32) -> Self {
+        self.conditions.push(Condition::Less {
+            name: name.to_owned(),
+            value,
+        });
+        self
+    }
+
+    pub fn on_end(mut self) -> Self {
+        self.conditions.push(Condition::AnimationEnd);
+        self
+    }
+
+    pub fn wait_for_end(mut self) -> Self {
+        self.wait_for_end = true;
+        self
+    }
+
+    pub fn keep_normalized_time(mut self) -> Self {
+        self.keep_normalized_time = true;
+        self
+    }
+
+    fn is_from(&self, state: &str) -> bool {
+        match self.from.as_ref() {
+            Some(from) => from == state,
+            None => self.to != state,
+        }
+    }
+}
+
+/// States mapped to animations, played on a `Sprite`. Transitions are checked in the order they were added,
+/// after every sprite update, and the first one that holds is taken. A `wait_for_end` transition that holds
+/// blocks the ones after it until the animation ends, and is dropped when its conditions stop holding.
+pub struct AnimationStateMachine {
+    sprite: Sprite,
+    states: HashMap<String, Animation>,
+    transitions: Vec<StateTransition>,
+    bools: HashMap<String, bool>,
+    floats: HashMap<String, f32>,
+    current: Option<String>,
+}
+
+impl Default for AnimationStateMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnimationStateMachine {
+    pub fn new() -> Self {
+        Self {
+            sprite: Sprite::new(),
+            states: HashMap::new(),
+            transitions: vec![],
+            bools: HashMap::new(),
+            floats: HashMap::new(),
+            current: None,
+        }
+    }
+
+    pub fn add_state(&mut self, name: &str, animation: &Animation) {
+        self.states.insert(name.to_owned(), animation.clone());
+    }
+
+    /// Use the animation from `crate::ANIMATION_MANAGER`. It return false when there is no such animation.
+    pub fn add_state_from(&mut self, name: &str, animation_name: &str) -> bool {
+        let animation = match crate::ANIMATION_MANAGER.lock().unwrap().get(animation_name) {
+            Some(v) => v.clone(),
+            None => {
+                println!(
+                    "AnimationStateMachine: add_state_from(): unknown animation: {}",
+                    animation_name
+                );
+                return false;
+            }
+        };
+        self.states.insert(name.to_owned(), animation);
+        true
+    }
+
+    pub fn add_transition(&mut self, transition: StateTransition) {
+        self.transitions.push(transition);
+    }
+
+    /// Play the state from its first frame. It return false when the state doesn't exist.
+    pub fn start(&mut self, state: &str) -> bool {
+        match self.states.get(state) {
+            Some(animation) => {
+                self.sprite.play(animation);
+                self.current = Some(state.to_owned());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn current_state(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.bools.insert(name.to_owned(), value);
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        self.floats.insert(name.to_owned(), value);
+    }
+
+    /// Unset parameters are false.
+    pub fn get_bool(&self, name: &str) -> bool {
+        self.bools.get(name).copied().unwrap_or(false)
+    }
+
+    /// Unset parameters are 0.0.
+    pub fn get_float(&self, name: &str) -> f32 {
+        self.floats.get(name).copied().unwrap_or(0.0)
+    }
+
+    pub fn sprite(&self) -> &Sprite {
+        &self.sprite
+    }
+
+    pub fn sprite_mut(&mut self) -> &mut Sprite {
+        &mut self.sprite
+    }
+
+    /// Update the sprite, then take a transition. It return true when the state changed.
+    pub fn update(&mut self, time: &FrameTime) -> bool {
+        self.sprite.update(time);
+
+        let state = match self.current.clone() {
+            Some(v) => v,
+            None => return false,
+        };
+
+        let is_end = self.sprite.is_end_of_animation()
+            || self
+                .sprite
+                .events()
+                .iter()
+                .any(|v| matches!(v, SpriteEvent::Looped | SpriteEvent::Completed));
+
+        let index = match self
+            .transitions
+            .iter()
+            .position(|v| v.is_from(&state) && self.holds(v, is_end))
+        {
+            Some(v) => v,
+            None => return false,
+        };
+        if self.transitions[index].wait_for_end && !is_end {
+            return false;
+        }
+
+        self.change(index)
+    }
+
+    fn holds(&self, transition: &StateTransition, is_end: bool) -> bool {
+        transition
+            .conditions
+            .iter()
+            .all(|condition| match condition {
+                Condition::Bool { name, value } => self.get_bool(name) == *value,
+                Condition::Greater { name, value } => self.get_float(name) > *value,
+                Condition::Less { name, value } => self.get_float(name) < *value,
+                Condition::AnimationEnd => is_end,
+            })
+    }
+
+    fn change(&mut self, index: usize) -> bool {
+        let transition = &self.transitions[index];
+        let animation = match self.states.get(&transition.to) {
+            Some(v) => v,
+            None => {
+                println!("AnimationStateMachine: unknown state: {}", transition.to);
+                return false;
+            }
+        };
+
+        let time = self.sprite.normalized_time();
+        self.sprite.play(animation);
+        if transition.keep_normalized_time {
+            self.sprite.seek_normalized(time);
+        }
+        self.current = Some(transition.to.clone());
+        true
+    }
+
+    pub fn draw(&self, position: Vec2, rotation: f32) {
+        self.sprite.draw(position, rotation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{test_animation, TEST_FRAME_LENGTH};
+
+    // idle -> run waits for the end of idle. hit can interrupt anything.
+    fn state_machine() -> AnimationStateMachine {
+        let mut state_machine = AnimationStateMachine::new();
+        state_machine.add_state("idle", &test_animation("idle", 4));
+        state_machine.add_state("run", &test_animation("run", 4));
+        state_machine.add_state("hit", &test_animation("hit", 2));
+        state_machine.add_transition(StateTransition::any("hit").when_bool("hurt", true));
+        state_machine.add_transition(
+            StateTransition::new("idle", "run")
+                .when_float_greater("speed", 0.1)
+                .wait_for_end(),
+        );
+        state_machine.start("idle");
+        state_machine
+    }
+
+    #[test]
+    fn wait_for_end_changes_at_the_end_of_the_animation() {
+        let mut state_machine = state_machine();
+        let time = FrameTime::new(TEST_FRAME_LENGTH);
+        state_machine.set_float("speed", 1.0);
+
+        let mut updates = 0;
+        while state_machine.current_state() == Some("idle") && updates < 10 {
+            state_machine.update(&time);
+            updates += 1;
+        }
+        assert_eq!(state_machine.current_state(), Some("run"));
+        assert!(updates >= 4, "changed after {} updates", updates);
+    }
+
+    #[test]
+    fn wait_for_end_is_dropped_when_its_conditions_stop_holding() {
+        let mut state_machine = state_machine();
+        let time = FrameTime::new(TEST_FRAME_LENGTH);
+        state_machine.set_float("speed", 1.0);
+        state_machine.update(&time);
+
+        state_machine.set_float("speed", 0.0);
+        for _ in 0..10 {
+            state_machine.update(&time);
+        }
+        assert_eq!(state_machine.current_state(), Some("idle"));
+    }
+
+    #[test]
+    fn earlier_transitions_are_taken_while_waiting_for_the_end() {
+        let mut state_machine = state_machine();
+        let time = FrameTime::new(TEST_FRAME_LENGTH);
+        state_machine.set_float("speed", 1.0);
+        state_machine.update(&time);
+
+        state_machine.set_bool("hurt", true);
+        assert!(state_machine.update(&time));
+        assert_eq!(state_machine.current_state(), Some("hit"));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::clock::{Clock, FixedTimestep, SystemClock};
+use crate::loading_scene::{AssetJob, LoadingProgress, LoadingScene};
+use crate::scene::GameScene;
+use crate::scene_manager::{SceneErrorPolicy, SceneManager};
+use crate::screen_scaler::ScreenScaler;
+use crate::time::FrameTime;
+use macroquad::prelude::*;
+use macroquad::window;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+type AssetLoader = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()>>>>;
+type SceneBuilder = Box<dyn FnOnce() -> Box<dyn GameScene>>;
+type LoadingDraw = Box<dyn FnMut(&LoadingProgress)>;
+
+static VIRTUAL_WIDTH: AtomicU32 = AtomicU32::new(0);
+static VIRTUAL_HEIGHT: AtomicU32 = AtomicU32::new(0);
+
+/// Virtual resolution of the running `App`. Everything is drawn in this size before scaling to the window.
+pub fn virtual_size() -> Vec2 {
+    vec2(
+        VIRTUAL_WIDTH.load(Ordering::Relaxed) as f32,
+        VIRTUAL_HEIGHT.load(Ordering::Relaxed) as f32,
+    )
+}
+
+/// Stop the game when `App::strict_assets()` is on and any asset failed to load.
+pub(crate) fn check_strict_assets() {
+    let texture_manager = crate::TEXTURE_MANAGER.lock().unwrap();
+    if texture_manager.is_strict() && !texture_manager.errors().is_empty() {
+        for e in texture_manager.errors().iter() {
+            println!("App: strict assets: {}", e);
+        }
+        panic!(
+            "App: strict assets: {} asset errors",
+            texture_manager.errors().len()
+        );
+    }
+}
+
+/// Builder for the game window and the fixed-timestep loop.
+///
+/// ```ignore
+/// App::new("Grow")
+///     .virtual_size(300, 800)
+///     .tick_rate(60)
+///     .assets(load_assets)
+///     .initial_scene(|| Box::new(TitleScene::new()))
+///     .run();
+/// ```
+pub struct App {
+    width: u32,
+    height: u32,
+    tick_rate: u64,
+    conf: window::Conf,
+    error_policy: SceneErrorPolicy,
+    clock: Box<dyn Clock>,
+    quit_key: Option<KeyCode>,
+    strict_assets: bool,
+    asset_loader: Option<AssetLoader>,
+    loading_jobs: Vec<AssetJob>,
+    loading_draw: Option<LoadingDraw>,
+    initial_scene: Option<SceneBuilder>,
+    #[cfg(feature = "hot-reload")]
+    hot_reload: Option<String>,
+    #[cfg(feature = "embedded-assets")]
+    embedded: Vec<(String, &'static include_dir::Dir<'static>)>,
+}
+
+impl App {
+    pub fn new(title: &str) -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            tick_rate: 60,
+            conf: window::Conf {
+                window_title: title.to_owned(),
+                window_width: 800,
+                window_height: 600,
+                ..Default::default()
+            },
+            error_policy: SceneErrorPolicy::Log,
+            clock: Box::new(SystemClock::new()),
+            quit_key: Some(KeyCode::Escape),
+            strict_assets: false,
+            asset_loader: None,
+            loading_jobs: vec![],
+            loading_draw: None,
+            initial_scene: None,
+            #[cfg(feature = "hot-reload")]
+            hot_reload: None,
+            #[cfg(feature = "embedded-assets")]
+            embedded: vec![],
+        }
+    }
+
+    /// Size of the render target that scenes draw on. It also become the initial window size.
+    pub fn virtual_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self.conf.window_width = width as i32;
+        self.conf.window_height = height as i32;
+        self
+    }
+
+    /// Number of fixed updates per second.
+    pub fn tick_rate(mut self, updates_per_second: u64) -> Self {
+        self.tick_rate = updates_per_second.max(1);
+        self
+    }
+
+    pub fn window_size(mut self, width: i32, height: i32) -> Self {
+        self.conf.window_width = width;
+        self.conf.window_height = height;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.conf.window_resizable = resizable;
+        self
+    }
+
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.conf.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn high_dpi(mut self, high_dpi: bool) -> Self {
+        self.conf.high_dpi = high_dpi;
+        self
+    }
+
+    /// Replace every window setting. Call it before the other window setters.
+    pub fn window_conf(mut self, conf: window::Conf) -> Self {
+        self.conf = conf;
+        self
+    }
+
+    pub fn error_policy(mut self, policy: SceneErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+
+    /// Time source of the loop. `SystemClock` by default.
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Key that ask the active scene to quit. `Escape` by default. `None` mean only scenes can quit.
+    pub fn quit_key(mut self, key: Option<KeyCode>) -> Self {
+        self.quit_key = key;
+        self
+    }
+
+    /// Stop the startup when any texture or animation frame fails to load in the asset hook.
+    pub fn strict_assets(mut self, strict: bool) -> Self {
+        self.strict_assets = strict;
+        self
+    }
+
+    /// Async hook that run once after the window is created and before the first scene.
+    pub fn assets<F, Fut>(mut self, loader: F) -> Self
+    where
+        F: FnOnce() -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.asset_loader = Some(Box::new(move || Box::pin(loader())));
+        self
+    }
+
+    /// Watch the directory and reload the textures, manifests and Aseprite files that change in it.
+    /// It is for development only.
+    #[cfg(feature = "hot-reload")]
+    pub fn hot_reload(mut self, directory: &str) -> Self {
+        self.hot_reload = Some(directory.to_owned());
+        self
+    }
+
+    /// Load the files under `mount` (e.g. "./resources") from `dir` before trying the disk.
+    ///
+    /// ```ignore
+    /// static RESOURCES: Dir = include_dir!("$CARGO_MANIFEST_DIR/resources");
+    /// App::new("Grow").embedded_assets("./resources", &RESOURCES)
+    /// ```
+    #[cfg(feature = "embedded-assets")]
+    pub fn embedded_assets(mut self, mount: &str, dir: &'static include_dir::Dir<'static>) -> Self {
+        self.embedded.push((mount.to_owned(), dir));
+        self
+    }
+
+    /// Load the jobs in a `LoadingScene` that shows the progress, after `assets()` and before the first scene.
+    pub fn loading(mut self, jobs: Vec<AssetJob>) -> Self {
+        self.loading_jobs.extend(jobs);
+        self
+    }
+
+    /// Draw the loading progress. See `loading_scene::draw_loading_progress()` for the default.
+    pub fn loading_draw<F>(mut self, draw: F) -> Self
+    where
+        F: FnMut(&LoadingProgress) + 'static,
+    {
+        self.loading_draw = Some(Box::new(draw));
+        self
+    }
+
+    /// Create the first scene. It is called after the assets are loaded.
+    pub fn initial_scene<F>(mut self, builder: F) -> Self
+    where
+        F: FnOnce() -> Box<dyn GameScene> + 'static,
+    {
+        self.initial_scene = Some(Box::new(builder));
+        self
+    }
+
+    /// Open the window and run the loop until the scene stack quit.
+    pub fn run(mut self) {
+        let conf = std::mem::take(&mut self.conf);
+        macroquad::Window::from_config(conf, self.main_loop());
+    }
+
+    async fn main_loop(mut self) {
+        VIRTUAL_WIDTH.store(self.width, Ordering::Relaxed);
+        VIRTUAL_HEIGHT.store(self.height, Ordering::Relaxed);
+
+        let tick_rate = Duration::from_secs_f64(1.0 / self.tick_rate as f64);
+
+        let mut scaler = ScreenScaler::new(self.width, self.height);
+        let mut scene_manager = SceneManager::new(self.width, self.height);
+        scene_manager.set_error_policy(self.error_policy);
+        scene_manager.set_quit_key(self.quit_key);
+
+        // Closing the window goes through `SceneManager::request_quit()`, so scenes can veto it.
+        prevent_quit();
+
+        crate::TEXTURE_MANAGER
+            .lock()
+            .unwrap()
+            .set_strict(self.strict_assets);
+
+        #[cfg(feature = "embedded-assets")]
+        for (mount, dir) in self.embedded.drain(..) {
+            crate::file::embed_dir(&mount, dir);
+        }
+
+        if let Some(loader) = self.asset_loader.take() {
+            loader().await;
+        }
+
+        check_strict_assets();
+
+        #[cfg(feature = "hot-reload")]
+        let hot_reload = match self.hot_reload.as_ref() {
+            Some(directory) => match crate::hot_reload::HotReload::new(directory) {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    println!("App: hot reload: {}: {}", directory, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let jobs = std::mem::take(&mut self.loading_jobs);
+        match (self.initial_scene.take(), jobs.is_empty()) {
+            (Some(builder), true) => scene_manager.push(builder()),
+            (Some(builder), false) => {
+                let mut scene = LoadingScene::new(jobs, builder);
+                if let Some(draw) = self.loading_draw.take() {
+                    scene = scene.on_draw(draw);
+                }
+                scene_manager.push(Box::new(scene));
+            }
+            (None, _) => {}
+        }
+
+        let mut frame_time = FrameTime::new(tick_rate);
+        let mut timestep = FixedTimestep::new(tick_rate, self.clock.now());
+        loop {
+            if is_quit_requested() {
+                scene_manager.request_quit();
+            }
+            scene_manager.check_quit_key();
+
+            #[cfg(feature = "hot-reload")]
+            if let Some(hot_reload) = hot_reload.as_ref() {
+                hot_reload.update().await;
+            }
+
+            for _ in 0..timestep.advance(self.clock.now()) {
+                frame_time.advance();
+                scene_manager.update(&frame_time);
+            }
+
+            if scene_manager.is_quit_requested() {
+                break;
+            }
+
+            // Keep the leftover for the next frame. Scenes use it to draw between two fixed updates.
+            let alpha = timestep.alpha();
+
+            scaler.begin();
+            scene_manager.draw(alpha);
+            scaler.end();
+
+            next_frame().await
+        }
+    }
+}
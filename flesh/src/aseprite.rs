@@ -0,0 +1,405 @@
+use crate::animation::{Animation, PlaybackMode};
+use crate::file::load_string;
+use crate::handle::Handle;
+use crate::textures::{load_image, TextureError};
+use macroquad::math::Rect;
+use macroquad::texture::Texture2D;
+use serde::de::{Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+/// Sheet data exported by Aseprite (`File > Export Sprite Sheet`, "JSON Data").
+/// Both "Hash" and "Array" formats are supported.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AsepriteSheet {
+    #[serde(deserialize_with = "deserialize_frames")]
+    pub frames: Vec<AsepriteFrame>,
+    pub meta: AsepriteMeta,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AsepriteFrame {
+    // Only the "Array" format has it in the frame. The "Hash" format use the key.
+    #[serde(default)]
+    pub filename: String,
+    pub frame: AsepriteRect,
+    // In milliseconds
+    pub duration: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct AsepriteRect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AsepriteMeta {
+    // Sheet image path, relative to the JSON file
+    pub image: String,
+    #[serde(default, rename = "frameTags")]
+    pub frame_tags: Vec<AsepriteTag>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AsepriteTag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    #[serde(default)]
+    pub direction: AsepriteDirection,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AsepriteDirection {
+    #[default]
+    Forward,
+    Reverse,
+    Pingpong,
+    PingpongReverse,
+}
+
+#[derive(Debug, Clone)]
+pub enum AsepriteError {
+    // The JSON file can't be read
+    Io { path: String, message: String },
+    // The JSON is not Aseprite sheet data
+    Parse { message: String },
+    // The sheet image can't be loaded or sliced
+    Texture { name: String, error: TextureError },
+    // The tag use frames that are not in the sheet
+    InvalidTag { tag: String, message: String },
+}
+
+impl fmt::Display for AsepriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsepriteError::Io { path, message } => {
+                write!(f, "can't read aseprite file: {}: {}", path, message)
+            }
+            AsepriteError::Parse { message } => {
+                write!(f, "can't parse aseprite file: {}", message)
+            }
+            AsepriteError::Texture { name, error } => {
+                write!(f, "aseprite texture: {}: {}", name, error)
+            }
+            AsepriteError::InvalidTag { tag, message } => {
+                write!(f, "aseprite tag: {}: {}", tag, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsepriteError {}
+
+// Keep the "Hash" format frames in file order. A map would sort "run 10" before "run 2".
+fn deserialize_frames<'de, D>(deserializer: D) -> Result<Vec<AsepriteFrame>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct FramesVisitor;
+
+    impl<'de> Visitor<'de> for FramesVisitor {
+        type Value = Vec<AsepriteFrame>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a frame array or a frame map")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            let mut frames = vec![];
+            while let Some(frame) = seq.next_element()? {
+                frames.push(frame);
+            }
+            Ok(frames)
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut frames = vec![];
+            while let Some((filename, mut frame)) = map.next_entry::<String, AsepriteFrame>()? {
+                frame.filename = filename;
+                frames.push(frame);
+            }
+            Ok(frames)
+        }
+    }
+
+    deserializer.deserialize_any(FramesVisitor)
+}
+
+impl AsepriteDirection {
+    /// Frame indices of `from..=to` in the order the animation keeps them, and how to play them.
+    pub fn playback(&self, from: usize, to: usize) -> (Vec<usize>, PlaybackMode) {
+        let forward: Vec<usize> = (from..=to).collect();
+        match self {
+            AsepriteDirection::Forward => (forward, PlaybackMode::Forward),
+            AsepriteDirection::Reverse => (forward, PlaybackMode::Reverse),
+            AsepriteDirection::Pingpong => (forward, PlaybackMode::PingPong),
+            AsepriteDirection::PingpongReverse => {
+                (forward.into_iter().rev().collect(), PlaybackMode::PingPong)
+            }
+        }
+    }
+}
+
+impl AsepriteSheet {
+    /// Parse the sheet data. Fail with `AsepriteError::InvalidTag` when a tag uses frames that are not in it.
+    pub fn from_json(json: &str) -> Result<Self, AsepriteError> {
+        let sheet: Self = serde_json::from_str(json).map_err(|e| AsepriteError::Parse {
+            message: e.to_string(),
+        })?;
+        sheet.check_tags()?;
+        Ok(sheet)
+    }
+
+    fn check_tags(&self) -> Result<(), AsepriteError> {
+        for tag in self.meta.frame_tags.iter() {
+            if tag.from > tag.to || tag.to >= self.frames.len() {
+                return Err(AsepriteError::InvalidTag {
+                    tag: tag.name.clone(),
+                    message: format!(
+                        "frames {}..={} are not in {} frames",
+                        tag.from,
+                        tag.to,
+                        self.frames.len()
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the JSON file. `meta.image` is changed to be relative to the working directory.
+    pub async fn load(path: &str) -> Result<Self, AsepriteError> {
+        let json = load_string(path).await.map_err(|e| AsepriteError::Io {
+            path: path.to_owned(),
+            message: e.to_string(),
+        })?;
+
+        let mut sheet = Self::from_json(&json)?;
+        if let Some(directory) = Path::new(path).parent() {
+            sheet.meta.image = directory
+                .join(&sheet.meta.image)
+                .to_string_lossy()
+                .into_owned();
+        }
+
+        Ok(sheet)
+    }
+
+    /// Region name of the frame at `index`.
+    pub fn frame_name(name: &str, index: usize) -> String {
+        format!("{}-{}", name, index)
+    }
+
+    /// Load the sheet image as `name` into `crate::TEXTURE_MANAGER`, with a region per frame,
+    /// then add an animation per tag into `crate::ANIMATION_MANAGER`.
+    /// Animations are named "{name}-{tag}". Without any tag, one animation `name` use every frame.
+    pub async fn register(&self, name: &str) -> Result<Vec<Handle<Animation>>, AsepriteError> {
+        self.register_with(name, false).await
+    }
+
+    /// Same as `register()`, but the sheet texture and the animations keep their handles.
+    pub async fn reload(&self, name: &str) -> Result<Vec<Handle<Animation>>, AsepriteError> {
+        self.register_with(name, true).await
+    }
+
+    async fn register_with(
+        &self,
+        name: &str,
+        reload: bool,
+    ) -> Result<Vec<Handle<Animation>>, AsepriteError> {
+        let texture_error = |error| AsepriteError::Texture {
+            name: name.to_owned(),
+            error,
+        };
+        // The fields are public, they may have changed since `from_json()`.
+        self.check_tags()?;
+
+        // Don't keep TEXTURE_MANAGER locked while waiting for the file.
+        let image = match load_image(&self.meta.image).await {
+            Ok(v) => v,
+            Err(e) => {
+                crate::TEXTURE_MANAGER.lock().unwrap().report(e.clone());
+                return Err(texture_error(e));
+            }
+        };
+
+        {
+            let mut texture_manager = crate::TEXTURE_MANAGER.lock().unwrap();
+            let texture = Texture2D::from_image(&image);
+            if reload && texture_manager.get_texture_handle(name).is_some() {
+                texture_manager
+                    .replace(name, texture)
+                    .map_err(texture_error)?;
+                texture_manager.remove_regions(name);
+            } else if let Err(e) = texture_manager.add(name, texture) {
+                // The manager didn't take it, free the upload.
+                texture.delete();
+                return Err(texture_error(e));
+            }
+            for (index, frame) in self.frames.iter().enumerate() {
+                let source = Rect::new(frame.frame.x, frame.frame.y, frame.frame.w, frame.frame.h);
+                texture_manager
+                    .add_region(&Self::frame_name(name, index), name, source)
+                    .map_err(texture_error)?;
+            }
+        }
+
+        let mut handles = vec![];
+        if self.meta.frame_tags.is_empty() && !self.frames.is_empty() {
+            let order: Vec<usize> = (0..self.frames.len()).collect();
+            handles.push(self.add_animation(name, name, &order, PlaybackMode::Forward)?);
+        }
+
+        for tag in self.meta.frame_tags.iter() {
+            let animation_name = format!("{}-{}", name, tag.name);
+            let (order, mode) = tag.direction.playback(tag.from, tag.to);
+            handles.push(self.add_animation(name, &animation_name, &order, mode)?);
+        }
+
+        Ok(handles)
+    }
+
+    fn add_animation(
+        &self,
+        name: &str,
+        animation_name: &str,
+        order: &[usize],
+        mode: PlaybackMode,
+    ) -> Result<Handle<Animation>, AsepriteError> {
+        let frame_names: Vec<String> = order.iter().map(|i| Self::frame_name(name, *i)).collect();
+        let mut animation = Animation::from_frames(
+            animation_name,
+            frame_names.iter().map(|v| v.as_str()).collect(),
+        )
+        .map_err(|error| AsepriteError::Texture {
+            name: name.to_owned(),
+            error,
+        })?;
+
+        for (frame, index) in animation.frames.iter_mut().zip(order.iter()) {
+            frame.duration = Some(Duration::from_millis(self.frames[*index].duration));
+        }
+        animation.mode = mode;
+
+        Ok(crate::ANIMATION_MANAGER.lock().unwrap().insert(animation))
+    }
+}
+
+/// Read Aseprite sheet data and register it. See `AsepriteSheet::register()`.
+pub async fn load_aseprite(
+    name: &str,
+    json_path: &str,
+) -> Result<Vec<Handle<Animation>>, AsepriteError> {
+    let sheet = AsepriteSheet::load(json_path).await?;
+
+    #[cfg(feature = "hot-reload")]
+    crate::hot_reload::watch_aseprite(name, json_path, &sheet);
+
+    sheet.register(name).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = r#"{
+        "frames": {
+            "run 2": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+            "run 10": { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "duration": 120 },
+            "run 1": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 80 }
+        },
+        "meta": {
+            "image": "run.png",
+            "frameTags": [
+                { "name": "loop", "from": 0, "to": 2, "direction": "pingpong_reverse" }
+            ]
+        }
+    }"#;
+
+    const ARRAY: &str = r#"{
+        "frames": [
+            { "filename": "a", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "duration": 50 },
+            { "filename": "b", "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "duration": 60 }
+        ],
+        "meta": { "image": "a.png" }
+    }"#;
+
+    #[test]
+    fn hash_format_keeps_file_order() {
+        let sheet = AsepriteSheet::from_json(HASH).unwrap();
+        let names: Vec<&str> = sheet.frames.iter().map(|v| v.filename.as_str()).collect();
+        assert_eq!(names, vec!["run 2", "run 10", "run 1"]);
+
+        let durations: Vec<u64> = sheet.frames.iter().map(|v| v.duration).collect();
+        assert_eq!(durations, vec![100, 120, 80]);
+        assert_eq!(sheet.frames[1].frame.x, 32.0);
+        assert_eq!(
+            sheet.meta.frame_tags[0].direction,
+            AsepriteDirection::PingpongReverse
+        );
+    }
+
+    #[test]
+    fn array_format() {
+        let sheet = AsepriteSheet::from_json(ARRAY).unwrap();
+        let names: Vec<&str> = sheet.frames.iter().map(|v| v.filename.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(sheet.frames[1].duration, 60);
+        assert!(sheet.meta.frame_tags.is_empty());
+    }
+
+    #[test]
+    fn directions_map_to_playback_modes() {
+        assert_eq!(
+            AsepriteDirection::Forward.playback(1, 3),
+            (vec![1, 2, 3], PlaybackMode::Forward)
+        );
+        assert_eq!(
+            AsepriteDirection::Reverse.playback(1, 3),
+            (vec![1, 2, 3], PlaybackMode::Reverse)
+        );
+        assert_eq!(
+            AsepriteDirection::Pingpong.playback(1, 3),
+            (vec![1, 2, 3], PlaybackMode::PingPong)
+        );
+        assert_eq!(
+            AsepriteDirection::PingpongReverse.playback(1, 3),
+            (vec![3, 2, 1], PlaybackMode::PingPong)
+        );
+    }
+
+    #[test]
+    fn tags_outside_of_the_frames_are_rejected() {
+        for (from, to) in [(0, 3), (2, 1)] {
+            let json = HASH.replace(
+                r#""from": 0, "to": 2"#,
+                &format!(r#""from": {}, "to": {}"#, from, to),
+            );
+            assert!(matches!(
+                AsepriteSheet::from_json(&json),
+                Err(AsepriteError::InvalidTag { tag, .. }) if tag == "loop"
+            ));
+        }
+    }
+
+    #[test]
+    fn other_json_is_a_parse_error() {
+        assert!(matches!(
+            AsepriteSheet::from_json(r#"{ "frames": 3 }"#),
+            Err(AsepriteError::Parse { .. })
+        ));
+    }
+}
@@ -0,0 +1,386 @@
+use crate::handle::Handle;
+use crate::textures::TextureError;
+use macroquad::color::Color;
+use macroquad::math::Rect;
+use macroquad::texture::{Image, Texture2D};
+
+/// Page size and space between packed images, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasSettings {
+    pub page_width: u32,
+    pub page_height: u32,
+    // Empty pixels around every image, so linear filtering doesn't bleed the neighbours in
+    pub padding: u32,
+}
+
+impl AtlasSettings {
+    /// Pages are images, so their size must fit in `u16`.
+    pub fn validate(&self) -> Result<(), TextureError> {
+        let max = u16::MAX as u32;
+        let fits = |size: u32| (1..=max).contains(&size);
+        if !fits(self.page_width) || !fits(self.page_height) {
+            return Err(TextureError::InvalidAtlasSettings(format!(
+                "page size {}x{} must be 1 to {}",
+                self.page_width, self.page_height, max
+            )));
+        }
+        if self.padding > max {
+            return Err(TextureError::InvalidAtlasSettings(format!(
+                "padding {} is bigger than {}",
+                self.padding, max
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for AtlasSettings {
+    fn default() -> Self {
+        Self {
+            page_width: 2048,
+            page_height: 2048,
+            padding: 1,
+        }
+    }
+}
+
+/// Where the packer put a rect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub page: usize,
+    pub x: u32,
+    pub y: u32,
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    // Next free x on the shelf
+    x: u32,
+}
+
+struct Page {
+    shelves: Vec<Shelf>,
+    // Next free y for a new shelf
+    y: u32,
+}
+
+/// Shelf packer. Rects are placed from the tallest to the shortest, in rows, and a new page
+/// is opened when none of the pages has room. It return the placements in the same order as `sizes`.
+pub fn pack_rects(
+    sizes: &[(u32, u32)],
+    settings: &AtlasSettings,
+) -> Result<Vec<Placement>, (usize, String)> {
+    let padding = settings.padding;
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|a, b| {
+        let (aw, ah) = sizes[*a];
+        let (bw, bh) = sizes[*b];
+        bh.cmp(&ah).then(bw.cmp(&aw))
+    });
+
+    let mut pages: Vec<Page> = vec![];
+    let mut placements = vec![
+        Placement {
+            page: 0,
+            x: 0,
+            y: 0
+        };
+        sizes.len()
+    ];
+
+    for index in order {
+        let (width, height) = sizes[index];
+        if width + padding * 2 > settings.page_width || height + padding * 2 > settings.page_height
+        {
+            return Err((
+                index,
+                format!(
+                    "{}x{} doesn't fit a {}x{} page with padding {}",
+                    width, height, settings.page_width, settings.page_height, padding
+                ),
+            ));
+        }
+
+        let placement = pages.iter_mut().enumerate().find_map(|(page_index, page)| {
+            place(page, width, height, settings).map(|(x, y)| Placement {
+                page: page_index,
+                x,
+                y,
+            })
+        });
+
+        placements[index] = match placement {
+            Some(v) => v,
+            None => {
+                let mut page = Page {
+                    shelves: vec![],
+                    y: padding,
+                };
+                let (x, y) = place(&mut page, width, height, settings).unwrap();
+                pages.push(page);
+                Placement {
+                    page: pages.len() - 1,
+                    x,
+                    y,
+                }
+            }
+        };
+    }
+
+    Ok(placements)
+}
+
+fn place(page: &mut Page, width: u32, height: u32, settings: &AtlasSettings) -> Option<(u32, u32)> {
+    let padding = settings.padding;
+    for shelf in page.shelves.iter_mut() {
+        if height <= shelf.height && shelf.x + width + padding <= settings.page_width {
+            let x = shelf.x;
+            shelf.x += width + padding;
+            return Some((x, shelf.y));
+        }
+    }
+
+    if page.y + height + padding <= settings.page_height {
+        let y = page.y;
+        page.shelves.push(Shelf {
+            y,
+            height,
+            x: padding + width + padding,
+        });
+        page.y += height + padding;
+        return Some((padding, y));
+    }
+
+    None
+}
+
+/// Packed image: the page and the rect on it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AtlasEntry {
+    pub name: String,
+    pub page: usize,
+    pub rect: Rect,
+}
+
+/// Atlas pages and entries on CPU side. Upload it with `TextureManager::add_atlas()`.
+pub struct Atlas {
+    pub pages: Vec<Image>,
+    pub entries: Vec<AtlasEntry>,
+}
+
+impl Atlas {
+    /// Pack the images into pages. The entries are in the same order as `images`.
+    pub fn build(
+        images: &[(String, Image)],
+        settings: &AtlasSettings,
+    ) -> Result<Self, TextureError> {
+        settings.validate()?;
+
+        let sizes: Vec<(u32, u32)> = images
+            .iter()
+            .map(|(_, image)| (image.width as u32, image.height as u32))
+            .collect();
+
+        let placements =
+            pack_rects(&sizes, settings).map_err(|(index, message)| TextureError::AtlasFull {
+                name: images[index].0.clone(),
+                message,
+            })?;
+
+        let page_count = placements.iter().map(|v| v.page + 1).max().unwrap_or(0);
+        let mut pages: Vec<Image> = (0..page_count)
+            .map(|_| {
+                Image::gen_image_color(
+                    settings.page_width as u16,
+                    settings.page_height as u16,
+                    Color::new(0.0, 0.0, 0.0, 0.0),
+                )
+            })
+            .collect();
+
+        let mut entries = vec![];
+        for ((name, image), placement) in images.iter().zip(placements.iter()) {
+            blit(&mut pages[placement.page], image, placement.x, placement.y);
+            entries.push(AtlasEntry {
+                name: name.clone(),
+                page: placement.page,
+                rect: Rect::new(
+                    placement.x as f32,
+                    placement.y as f32,
+                    image.width as f32,
+                    image.height as f32,
+                ),
+            });
+        }
+
+        Ok(Self { pages, entries })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AtlasEntry> {
+        self.entries.iter().find(|v| v.name == name)
+    }
+}
+
+// Copy RGBA8 rows of `image` into `page` at (x, y).
+fn blit(page: &mut Image, image: &Image, x: u32, y: u32) {
+    let page_stride = page.width as usize * 4;
+    let row_length = image.width as usize * 4;
+    for row in 0..image.height as usize {
+        let from = row * row_length;
+        let to = (y as usize + row) * page_stride + x as usize * 4;
+        page.bytes[to..to + row_length].copy_from_slice(&image.bytes[from..from + row_length]);
+    }
+}
+
+/// Atlas rect of a texture that was packed. See `TextureManager::resolve()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasSlot {
+    pub page: Handle<Texture2D>,
+    pub rect: Rect,
+}
+
+impl AtlasSlot {
+    /// Move a source rect of the original texture onto the page. `None` is the whole texture.
+    pub fn map(&self, source: Option<Rect>) -> Rect {
+        match source {
+            Some(source) => Rect::new(
+                self.rect.x + source.x,
+                self.rect.y + source.y,
+                source.w,
+                source.h,
+            ),
+            None => self.rect,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handle::Assets;
+    use macroquad::color::{BLUE, RED, WHITE};
+
+    fn image(width: u16, height: u16, color: Color) -> Image {
+        Image::gen_image_color(width, height, color)
+    }
+
+    fn overlaps(a: &Rect, b: &Rect) -> bool {
+        a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h
+    }
+
+    #[test]
+    fn pack_rects_keeps_padding_and_order() {
+        let settings = AtlasSettings {
+            page_width: 64,
+            page_height: 64,
+            padding: 1,
+        };
+        let sizes = [(10, 10), (20, 30), (16, 16), (8, 30)];
+        let placements = pack_rects(&sizes, &settings).unwrap();
+        assert_eq!(placements.len(), sizes.len());
+
+        let rects: Vec<Rect> = placements
+            .iter()
+            .zip(sizes.iter())
+            .map(|(p, (w, h))| Rect::new(p.x as f32, p.y as f32, *w as f32, *h as f32))
+            .collect();
+        for (index, rect) in rects.iter().enumerate() {
+            assert_eq!(placements[index].page, 0);
+            assert!(rect.x >= 1.0 && rect.y >= 1.0);
+            assert!(rect.right() + 1.0 <= 64.0 && rect.bottom() + 1.0 <= 64.0);
+
+            // Grow by the padding, so touching rects count as overlapping
+            let padded = Rect::new(rect.x - 0.5, rect.y - 0.5, rect.w + 1.0, rect.h + 1.0);
+            for other in rects.iter().skip(index + 1) {
+                assert!(!overlaps(&padded, other), "{:?} {:?}", rect, other);
+            }
+        }
+    }
+
+    #[test]
+    fn pack_rects_opens_new_pages() {
+        let settings = AtlasSettings {
+            page_width: 32,
+            page_height: 32,
+            padding: 0,
+        };
+        let placements = pack_rects(&[(32, 32), (32, 32), (16, 16)], &settings).unwrap();
+        let pages: Vec<usize> = placements.iter().map(|v| v.page).collect();
+        assert_eq!(pages, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn pack_rects_rejects_too_big_rects() {
+        let settings = AtlasSettings {
+            page_width: 32,
+            page_height: 32,
+            padding: 1,
+        };
+        let error = pack_rects(&[(8, 8), (31, 8)], &settings).unwrap_err();
+        assert_eq!(error.0, 1);
+    }
+
+    #[test]
+    fn build_copies_pixels_into_pages() {
+        let settings = AtlasSettings {
+            page_width: 16,
+            page_height: 16,
+            padding: 1,
+        };
+        let images = vec![
+            ("red".to_owned(), image(4, 2, RED)),
+            ("blue".to_owned(), image(3, 5, BLUE)),
+        ];
+        let atlas = Atlas::build(&images, &settings).unwrap();
+        assert_eq!(atlas.pages.len(), 1);
+        assert_eq!(atlas.entries.len(), 2);
+
+        let page = &atlas.pages[0];
+        assert_eq!((page.width, page.height), (16, 16));
+        for (name, source) in images.iter() {
+            let entry = atlas.get(name).unwrap();
+            assert_eq!(
+                (entry.rect.w, entry.rect.h),
+                (source.width as f32, source.height as f32)
+            );
+
+            let (x, y) = (entry.rect.x as u32, entry.rect.y as u32);
+            assert_eq!(page.get_pixel(x, y), source.get_pixel(0, 0));
+            let (right, bottom) = (x + source.width as u32 - 1, y + source.height as u32 - 1);
+            assert_eq!(page.get_pixel(right, bottom), source.get_pixel(0, 0));
+        }
+        // Padding stays empty
+        assert_eq!(page.get_pixel(0, 0).a, 0.0);
+    }
+
+    #[test]
+    fn build_rejects_page_sizes_that_dont_fit_an_image() {
+        let images = vec![("a".to_owned(), image(4, 4, WHITE))];
+        for (page_width, page_height) in [(70000, 16), (16, 0)] {
+            let settings = AtlasSettings {
+                page_width,
+                page_height,
+                padding: 0,
+            };
+            assert!(matches!(
+                Atlas::build(&images, &settings),
+                Err(TextureError::InvalidAtlasSettings(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn slot_maps_source_rects_onto_the_page() {
+        let mut textures = Assets::new();
+        let slot = AtlasSlot {
+            page: textures.insert(Texture2D::empty()),
+            rect: Rect::new(10.0, 20.0, 32.0, 16.0),
+        };
+        assert_eq!(slot.map(None), slot.rect);
+        assert_eq!(
+            slot.map(Some(Rect::new(8.0, 4.0, 8.0, 8.0))),
+            Rect::new(18.0, 24.0, 8.0, 8.0)
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+/// Source of time for the fixed-timestep loop.
+pub trait Clock {
+    /// Time since the clock was created.
+    fn now(&self) -> Duration;
+}
+
+/// Real time clock. `App` use it by default.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Clock that only move when `advance()` is called. Used by `HeadlessRunner` and for tests.
+#[derive(Default)]
+pub struct ManualClock {
+    now: Duration,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Duration::ZERO,
+        }
+    }
+
+    pub fn advance(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now
+    }
+}
+
+/// Turn the time from a `Clock` into the number of fixed updates to run.
+pub struct FixedTimestep {
+    tick_rate: Duration,
+    // Slightly shorter than `tick_rate`, so vsync jitter doesn't alternate 0 and 2 updates per frame
+    early_tick_rate: Duration,
+    last_time: Duration,
+    time_passed: Duration,
+}
+
+impl FixedTimestep {
+    pub fn new(tick_rate: Duration, now: Duration) -> Self {
+        let updates_per_second = 1.0 / tick_rate.as_secs_f64();
+        Self {
+            tick_rate,
+            early_tick_rate: Duration::from_secs_f64(1.0 / (updates_per_second + 2.0)),
+            last_time: now,
+            time_passed: Duration::ZERO,
+        }
+    }
+
+    /// Add the time since the last call and return how many fixed updates should run.
+    pub fn advance(&mut self, now: Duration) -> u32 {
+        let diff_time = now.saturating_sub(self.last_time);
+        self.last_time = now;
+
+        // The update without draw can't happen more than 8 frames
+        // https://medium.com/@tglaiel/how-to-make-your-game-run-at-60fps-24c61210fe75
+        self.time_passed = (self.time_passed + diff_time).min(self.tick_rate * 8);
+
+        // A frame that is a bit shorter than the tick still run one update. The missing time is
+        // snapped away, the rest is kept for the next frame and `alpha()`.
+        let mut ticks = 0;
+        while self.time_passed >= self.early_tick_rate {
+            self.time_passed = self.time_passed.saturating_sub(self.tick_rate);
+            ticks += 1;
+        }
+        ticks
+    }
+
+    /// Leftover time as a fraction of one fixed update (0.0 - 1.0). Scenes use it to draw between two updates.
+    pub fn alpha(&self) -> f32 {
+        (self.time_passed.as_secs_f32() / self.tick_rate.as_secs_f32()).min(1.0)
+    }
+}
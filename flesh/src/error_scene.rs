@@ -0,0 +1,54 @@
+use crate::input;
+use crate::scene::{GameScene, SceneError, SceneTransition};
+use crate::time::FrameTime;
+use macroquad::prelude::*;
+
+const LINE_LENGTH: usize = 28;
+
+/// Built-in scene that show the `SceneError` on screen. Press `Space` or `Enter` to pop it.
+pub struct ErrorScene {
+    lines: Vec<String>,
+}
+
+impl ErrorScene {
+    pub fn new(error: &SceneError) -> Self {
+        let mut lines = vec![error.scene.clone()];
+        let mut line = String::new();
+        for word in error.message.split_whitespace() {
+            if !line.is_empty() && line.len() + word.len() + 1 > LINE_LENGTH {
+                lines.push(line);
+                line = String::new();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+
+        Self { lines }
+    }
+}
+
+impl GameScene for ErrorScene {
+    fn init(&mut self) {}
+
+    fn update(&mut self, _time: &FrameTime) -> Result<SceneTransition, SceneError> {
+        if input::is_key_pressed(KeyCode::Space) || input::is_key_pressed(KeyCode::Enter) {
+            return Ok(SceneTransition::Pop);
+        }
+
+        Ok(SceneTransition::None)
+    }
+
+    fn draw(&mut self, _alpha: f32) {
+        clear_background(WHITE);
+
+        crate::utils::draw_text_center("ERROR", 48, 100.0);
+        for (index, line) in self.lines.iter().enumerate() {
+            crate::utils::draw_text_center(line, 24, 200.0 + index as f32 * 30.0);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use macroquad::file::FileError;
+
+#[cfg(feature = "embedded-assets")]
+use include_dir::Dir;
+#[cfg(feature = "embedded-assets")]
+use std::path::{Component, Path, PathBuf};
+#[cfg(feature = "embedded-assets")]
+use std::sync::Mutex;
+
+#[cfg(feature = "embedded-assets")]
+lazy_static! {
+    // Embedded directories and the path they replace, e.g. "resources"
+    static ref EMBEDDED: Mutex<Vec<(PathBuf, &'static Dir<'static>)>> = Mutex::new(vec![]);
+}
+
+// "./resources/../resources/a.png" -> "resources/a.png"
+#[cfg(feature = "embedded-assets")]
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Serve files under `mount` (e.g. "./resources") from the directory embedded with `include_dir!`.
+/// Files that are not in it are still read from the disk.
+#[cfg(feature = "embedded-assets")]
+pub fn embed_dir(mount: &str, dir: &'static Dir<'static>) {
+    EMBEDDED
+        .lock()
+        .unwrap()
+        .push((normalize(Path::new(mount)), dir));
+}
+
+/// Content of the embedded file, if it is embedded.
+#[cfg(feature = "embedded-assets")]
+pub fn embedded_file(path: &str) -> Option<&'static [u8]> {
+    let path = normalize(Path::new(path));
+    let embedded = EMBEDDED.lock().unwrap();
+    for (mount, dir) in embedded.iter().rev() {
+        if let Ok(relative) = path.strip_prefix(mount) {
+            if let Some(file) = dir.get_file(relative) {
+                return Some(file.contents());
+            }
+        }
+    }
+    None
+}
+
+/// Same as `macroquad::file::load_file()`, but embedded files are read from memory first.
+/// Every asset loader of the crate use it.
+pub async fn load_file(path: &str) -> Result<Vec<u8>, FileError> {
+    #[cfg(feature = "embedded-assets")]
+    if let Some(bytes) = embedded_file(path) {
+        return Ok(bytes.to_vec());
+    }
+
+    macroquad::file::load_file(path).await
+}
+
+pub async fn load_string(path: &str) -> Result<String, FileError> {
+    let bytes = load_file(path).await?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
@@ -0,0 +1,208 @@
+use crate::clock::{Clock, FixedTimestep, ManualClock};
+use crate::input::{self, ScriptedInput};
+use crate::scene::GameScene;
+use crate::scene_manager::SceneManager;
+use crate::time::FrameTime;
+use macroquad::input::KeyCode;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug)]
+enum InputEvent {
+    Press(KeyCode),
+    Release(KeyCode),
+}
+
+/// Key presses and releases at fixed update numbers (`FrameTime::frame`, the first update is 1).
+#[derive(Default, Clone, Debug)]
+pub struct InputScript {
+    events: BTreeMap<u64, Vec<InputEvent>>,
+}
+
+impl InputScript {
+    pub fn new() -> Self {
+        Self {
+            events: BTreeMap::new(),
+        }
+    }
+
+    /// Hold `key` down from `frame`.
+    pub fn press(mut self, frame: u64, key: KeyCode) -> Self {
+        self.events
+            .entry(frame)
+            .or_default()
+            .push(InputEvent::Press(key));
+        self
+    }
+
+    /// Release `key` at `frame`.
+    pub fn release(mut self, frame: u64, key: KeyCode) -> Self {
+        self.events
+            .entry(frame)
+            .or_default()
+            .push(InputEvent::Release(key));
+        self
+    }
+
+    /// Press `key` at `frame` and release it on the next update.
+    pub fn tap(self, frame: u64, key: KeyCode) -> Self {
+        self.press(frame, key).release(frame + 1, key)
+    }
+}
+
+/// Run the scene stack without a window. Time come from `ManualClock` and keyboard from `InputScript`.
+/// Only `update` is called, so the scenes should not call macroquad's draw or window functions there.
+///
+/// ```ignore
+/// let mut runner = HeadlessRunner::new(60).script(InputScript::new().tap(10, KeyCode::Space));
+/// runner.push(Box::new(GameplayScene::new()));
+/// runner.step(600);
+/// let scene = runner.scene_manager().top::<GameplayScene>().unwrap();
+/// ```
+pub struct HeadlessRunner {
+    scene_manager: SceneManager,
+    clock: ManualClock,
+    timestep: FixedTimestep,
+    frame_time: FrameTime,
+    script: InputScript,
+}
+
+impl HeadlessRunner {
+    /// Create the runner and switch `flesh::input` to scripted keyboard on this thread until the runner is dropped.
+    /// Use one runner per thread.
+    pub fn new(updates_per_second: u64) -> Self {
+        let tick_rate = Duration::from_secs_f64(1.0 / updates_per_second.max(1) as f64);
+        let clock = ManualClock::new();
+        let timestep = FixedTimestep::new(tick_rate, clock.now());
+
+        input::set_scripted_input(Some(ScriptedInput::default()));
+
+        Self {
+            scene_manager: SceneManager::new(0, 0),
+            clock,
+            timestep,
+            frame_time: FrameTime::new(tick_rate),
+            script: InputScript::new(),
+        }
+    }
+
+    pub fn script(mut self, script: InputScript) -> Self {
+        self.script = script;
+        self
+    }
+
+    pub fn push(&mut self, scene: Box<dyn GameScene>) {
+        self.scene_manager.push(scene);
+    }
+
+    /// Run `ticks` fixed updates. It stops early when a scene request to quit.
+    pub fn step(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            if self.scene_manager.is_quit_requested() {
+                break;
+            }
+
+            self.clock.advance(self.frame_time.delta);
+            for _ in 0..self.timestep.advance(self.clock.now()) {
+                self.frame_time.advance();
+
+                if let Some(events) = self.script.events.get(&self.frame_time.frame) {
+                    input::with_scripted_input(|input| {
+                        for event in events.iter() {
+                            match *event {
+                                InputEvent::Press(key) => input.press(key),
+                                InputEvent::Release(key) => input.release(key),
+                            }
+                        }
+                    });
+                }
+
+                // Every tick is a rendered frame here.
+                self.scene_manager.check_quit_key();
+                self.scene_manager.update(&self.frame_time);
+                input::with_scripted_input(|input| input.end_frame());
+            }
+        }
+    }
+
+    pub fn frame_time(&self) -> &FrameTime {
+        &self.frame_time
+    }
+
+    pub fn scene_manager(&self) -> &SceneManager {
+        &self.scene_manager
+    }
+
+    pub fn scene_manager_mut(&mut self) -> &mut SceneManager {
+        &mut self.scene_manager
+    }
+}
+
+impl Drop for HeadlessRunner {
+    fn drop(&mut self) {
+        input::set_scripted_input(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::{SceneError, SceneTransition};
+    use std::sync::{Arc, Barrier};
+
+    struct CountScene {
+        presses: u64,
+    }
+
+    impl GameScene for CountScene {
+        fn init(&mut self) {}
+
+        fn update(&mut self, _time: &FrameTime) -> Result<SceneTransition, SceneError> {
+            if input::is_key_pressed(KeyCode::Space) {
+                self.presses += 1;
+            }
+            Ok(SceneTransition::None)
+        }
+
+        fn draw(&mut self, _alpha: f32) {}
+    }
+
+    // Step one tick at a time, in lockstep with the other threads.
+    fn count_presses(taps: u64, barrier: &Barrier) -> u64 {
+        let mut script = InputScript::new();
+        for index in 0..taps {
+            script = script.tap(1 + index * 4, KeyCode::Space);
+        }
+
+        let mut runner = HeadlessRunner::new(60).script(script);
+        runner.push(Box::new(CountScene { presses: 0 }));
+        for _ in 0..120 {
+            barrier.wait();
+            runner.step(1);
+        }
+        runner.scene_manager().top::<CountScene>().unwrap().presses
+    }
+
+    #[test]
+    fn step_runs_one_update_per_tick() {
+        let mut runner = HeadlessRunner::new(60);
+        runner.step(600);
+        assert_eq!(runner.frame_time().frame, 600);
+    }
+
+    #[test]
+    fn runners_on_other_threads_keep_their_own_input() {
+        let barrier = Arc::new(Barrier::new(3));
+        let threads: Vec<_> = (1..=3)
+            .map(|taps| {
+                let barrier = barrier.clone();
+                std::thread::spawn(move || (taps, count_presses(taps, &barrier)))
+            })
+            .collect();
+
+        for thread in threads {
+            let (taps, presses) = thread.join().unwrap();
+            assert_eq!(presses, taps);
+        }
+    }
+}
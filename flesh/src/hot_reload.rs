@@ -0,0 +1,141 @@
+use crate::aseprite::AsepriteSheet;
+use crate::manifest::AssetManifest;
+use crate::textures::load_image;
+use macroquad::texture::Texture2D;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+
+/// What to reload when a watched file changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    // Texture file loaded with this name
+    Texture(String),
+    // Manifest file. Everything in it is registered again.
+    Manifest,
+    // Aseprite JSON or its sheet image
+    Aseprite { name: String, json_path: String },
+}
+
+lazy_static! {
+    static ref SOURCES: Mutex<HashMap<PathBuf, Source>> = Mutex::new(HashMap::new());
+}
+
+// Notify gives absolute paths. The loaders use paths relative to the working directory.
+fn normalize(path: &Path) -> PathBuf {
+    match path.canonicalize() {
+        Ok(v) => v,
+        Err(_) => match std::env::current_dir() {
+            Ok(directory) => directory.join(path),
+            Err(_) => path.to_path_buf(),
+        },
+    }
+}
+
+/// Remember where the asset comes from. The loaders call it when the `hot-reload` feature is on.
+pub fn watch(path: &str, source: Source) {
+    SOURCES
+        .lock()
+        .unwrap()
+        .insert(normalize(Path::new(path)), source);
+}
+
+pub(crate) fn watch_aseprite(name: &str, json_path: &str, sheet: &AsepriteSheet) {
+    let source = Source::Aseprite {
+        name: name.to_owned(),
+        json_path: json_path.to_owned(),
+    };
+    watch(json_path, source.clone());
+    watch(&sheet.meta.image, source);
+}
+
+/// Watch a directory and reload the changed assets. `App::hot_reload()` polls it every frame.
+pub struct HotReload {
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+impl HotReload {
+    pub fn new(directory: &str) -> Result<Self, notify::Error> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+        watcher.watch(Path::new(directory), RecursiveMode::Recursive)?;
+        println!("HotReload: watching {}", directory);
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Files that were created or changed since the last call, without duplicates.
+    pub fn changed(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = vec![];
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Ok(event) => {
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        for path in event.paths.iter() {
+                            let path = normalize(path);
+                            if !paths.contains(&path) {
+                                paths.push(path);
+                            }
+                        }
+                    }
+                }
+                Err(e) => println!("HotReload: {}", e),
+            }
+        }
+        paths
+    }
+
+    /// Reload every changed asset. Handles stay the same, so `Sprite`s pick the change up by themselves.
+    pub async fn update(&self) {
+        for path in self.changed() {
+            let source = SOURCES.lock().unwrap().get(&path).cloned();
+            if let Some(source) = source {
+                reload(&path, &source).await;
+            }
+        }
+    }
+}
+
+async fn reload(path: &Path, source: &Source) {
+    let path_name = path.to_string_lossy();
+    println!("HotReload: reload {:?}: {}", source, path_name);
+
+    match source {
+        Source::Texture(name) => match load_image(&path_name).await {
+            Ok(image) => {
+                let _ = crate::TEXTURE_MANAGER
+                    .lock()
+                    .unwrap()
+                    .replace(name, Texture2D::from_image(&image));
+            }
+            // The file may be written half. The next change event try again.
+            Err(e) => println!("HotReload: {}", e),
+        },
+        Source::Manifest => match AssetManifest::load(&path_name).await {
+            Ok(manifest) => {
+                if let Err(errors) = manifest.reload().await {
+                    for e in errors.iter() {
+                        println!("HotReload: {}", e);
+                    }
+                }
+            }
+            Err(e) => println!("HotReload: {}", e),
+        },
+        Source::Aseprite { name, json_path } => match AsepriteSheet::load(json_path).await {
+            Ok(sheet) => {
+                if let Err(e) = sheet.reload(name).await {
+                    println!("HotReload: {}", e);
+                }
+            }
+            Err(e) => println!("HotReload: {}", e),
+        },
+    }
+}
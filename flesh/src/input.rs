@@ -0,0 +1,79 @@
+use macroquad::input::KeyCode;
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+/// Keyboard state that is set by code instead of the window. `HeadlessRunner` use it to play `InputScript`.
+#[derive(Default, Debug)]
+pub struct ScriptedInput {
+    down: HashSet<KeyCode>,
+    pressed: HashSet<KeyCode>,
+    released: HashSet<KeyCode>,
+}
+
+impl ScriptedInput {
+    pub fn press(&mut self, key: KeyCode) {
+        if self.down.insert(key) {
+            self.pressed.insert(key);
+        }
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        if self.down.remove(&key) {
+            self.released.insert(key);
+        }
+    }
+
+    /// Clear `pressed` and `released`. Call it after every update.
+    pub fn end_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+}
+
+thread_local! {
+    // Per thread, so headless runners in parallel tests don't share the keyboard
+    static SCRIPTED_INPUT: RefCell<Option<ScriptedInput>> = const { RefCell::new(None) };
+}
+
+/// Read the keyboard of the current thread from `ScriptedInput` instead of macroquad.
+/// Pass `None` to go back to macroquad.
+pub fn set_scripted_input(input: Option<ScriptedInput>) {
+    SCRIPTED_INPUT.with(|v| *v.borrow_mut() = input);
+}
+
+/// Change the scripted keyboard state. It does nothing when the input come from macroquad.
+pub fn with_scripted_input<F: FnOnce(&mut ScriptedInput)>(f: F) {
+    SCRIPTED_INPUT.with(|v| {
+        if let Some(input) = v.borrow_mut().as_mut() {
+            f(input);
+        }
+    });
+}
+
+// `None` when the input come from macroquad.
+fn scripted<F: FnOnce(&ScriptedInput) -> bool>(f: F) -> Option<bool> {
+    SCRIPTED_INPUT.with(|v| v.borrow().as_ref().map(f))
+}
+
+// Scenes should use the functions below instead of macroquad's, so they can run without a window.
+
+pub fn is_key_down(key: KeyCode) -> bool {
+    match scripted(|input| input.down.contains(&key)) {
+        Some(v) => v,
+        None => macroquad::input::is_key_down(key),
+    }
+}
+
+pub fn is_key_pressed(key: KeyCode) -> bool {
+    match scripted(|input| input.pressed.contains(&key)) {
+        Some(v) => v,
+        None => macroquad::input::is_key_pressed(key),
+    }
+}
+
+pub fn is_key_released(key: KeyCode) -> bool {
+    match scripted(|input| input.released.contains(&key)) {
+        Some(v) => v,
+        None => macroquad::input::is_key_released(key),
+    }
+}
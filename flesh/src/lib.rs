@@ -0,0 +1,35 @@
+use std::sync::Mutex;
+
+pub mod animation;
+pub mod animation_state;
+pub mod app;
+pub mod aseprite;
+pub mod atlas;
+pub mod clock;
+pub mod error_scene;
+pub mod file;
+pub mod handle;
+pub mod headless;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
+pub mod input;
+pub mod loading_scene;
+pub mod manifest;
+pub mod scene;
+pub mod scene_manager;
+pub mod screen_scaler;
+pub mod sprite;
+pub mod textures;
+pub mod time;
+pub mod transition;
+pub mod utils;
+
+use crate::animation::AnimationManager;
+use crate::textures::TextureManager;
+
+#[macro_use]
+extern crate lazy_static;
+lazy_static! {
+    pub static ref TEXTURE_MANAGER: Mutex<TextureManager> = Mutex::new(TextureManager::new());
+    pub static ref ANIMATION_MANAGER: Mutex<AnimationManager> = Mutex::new(AnimationManager::new());
+}
@@ -0,0 +1,332 @@
+use crate::aseprite::load_aseprite;
+use crate::manifest::{register_texture, AssetManifest, ManifestError};
+use crate::scene::{GameScene, SceneError, SceneTransition};
+use crate::time::FrameTime;
+use crate::transition::Transition;
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+// A job can give more jobs to run right after it, e.g. the files listed in a manifest.
+type JobFuture = Pin<Box<dyn Future<Output = Result<Vec<AssetJob>, String>>>>;
+type SceneBuilder = Box<dyn FnOnce() -> Box<dyn GameScene>>;
+type DrawProgress = Box<dyn FnMut(&LoadingProgress)>;
+
+/// One thing to load. The label is shown as the current file.
+pub struct AssetJob {
+    pub label: String,
+    start: Box<dyn FnOnce() -> JobFuture>,
+}
+
+impl AssetJob {
+    /// Custom job, e.g. generating a texture or loading a sound.
+    pub fn new<F, Fut>(label: &str, job: F) -> Self
+    where
+        F: FnOnce() -> Fut + 'static,
+        Fut: Future<Output = Result<(), String>> + 'static,
+    {
+        Self::with_jobs(label, move || async move { job().await.map(|_| vec![]) })
+    }
+
+    // The jobs that the job returns run next, before the other jobs.
+    fn with_jobs<F, Fut>(label: &str, job: F) -> Self
+    where
+        F: FnOnce() -> Fut + 'static,
+        Fut: Future<Output = Result<Vec<AssetJob>, String>> + 'static,
+    {
+        Self {
+            label: label.to_owned(),
+            start: Box::new(move || Box::pin(job())),
+        }
+    }
+
+    /// Load the texture into `crate::TEXTURE_MANAGER`.
+    pub fn texture(name: &str, path: &str) -> Self {
+        let name = name.to_owned();
+        let job_path = path.to_owned();
+        Self::new(path, move || async move {
+            register_texture(&name, &job_path, &None, false)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    /// Read the manifest, then load each texture, sheet and Aseprite file in it as its own job.
+    /// The animations are added by the last job. See `crate::manifest::AssetManifest`.
+    pub fn manifest(path: &str) -> Self {
+        let job_path = path.to_owned();
+        Self::with_jobs(path, move || async move {
+            let manifest = AssetManifest::load(&job_path)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            #[cfg(feature = "hot-reload")]
+            crate::hot_reload::watch(&job_path, crate::hot_reload::Source::Manifest);
+
+            let mut jobs = vec![];
+            for texture in manifest.textures.clone() {
+                jobs.push(Self::new(&texture.path.clone(), move || async move {
+                    texture.register(false).await.map_err(|e| e.to_string())
+                }));
+            }
+            for sheet in manifest.sheets.clone() {
+                jobs.push(Self::new(&sheet.path.clone(), move || async move {
+                    sheet.register(false).await.map_err(|e| join_errors(&e))
+                }));
+            }
+            for entry in manifest.aseprite.clone() {
+                jobs.push(Self::new(&entry.path.clone(), move || async move {
+                    entry.register(false).await.map_err(|e| e.to_string())
+                }));
+            }
+            jobs.push(Self::new(&job_path, move || async move {
+                manifest.register_animations().map_err(|e| join_errors(&e))
+            }));
+
+            Ok(jobs)
+        })
+    }
+
+    /// See `crate::aseprite::load_aseprite()`.
+    pub fn aseprite(name: &str, json_path: &str) -> Self {
+        let name = name.to_owned();
+        let job_path = json_path.to_owned();
+        Self::new(json_path, move || async move {
+            load_aseprite(&name, &job_path)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+fn join_errors(errors: &[ManifestError]) -> String {
+    errors
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+/// What `LoadingScene` gives to the draw callback.
+#[derive(Clone, Debug, Default)]
+pub struct LoadingProgress {
+    pub loaded: usize,
+    pub total: usize,
+    // Label of the job that is loading now. Jobs that finish right away leave the next or the last label.
+    pub current: Option<String>,
+    pub errors: Vec<String>,
+}
+
+impl LoadingProgress {
+    /// 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.loaded as f32 / self.total as f32
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.loaded >= self.total
+    }
+}
+
+/// Default look of `LoadingScene`: a progress bar and the current file.
+pub fn draw_loading_progress(progress: &LoadingProgress) {
+    clear_background(BLACK);
+
+    let size = crate::app::virtual_size();
+    let width = size.x * 0.8;
+    let x = (size.x - width) / 2.0;
+    let y = size.y / 2.0;
+    draw_rectangle_lines(x, y, width, 16.0, 2.0, WHITE);
+    draw_rectangle(x, y, width * progress.fraction(), 16.0, WHITE);
+
+    crate::utils::draw_text_center(
+        &format!("{} / {}", progress.loaded, progress.total),
+        24,
+        y - 20.0,
+    );
+    if let Some(current) = progress.current.as_ref() {
+        crate::utils::draw_text_center(current, 16, y + 48.0);
+    }
+}
+
+/// Built-in scene that run `AssetJob`s across frames, then replace itself with the next scene.
+///
+/// ```ignore
+/// let scene = LoadingScene::new(vec![AssetJob::manifest("./resources/manifest.json")], || {
+///     Box::new(TitleScene::new())
+/// });
+/// ```
+pub struct LoadingScene {
+    jobs: VecDeque<AssetJob>,
+    current: Option<JobFuture>,
+    progress: LoadingProgress,
+    // Time spent on loading per update. The rest of the frame is for drawing the progress.
+    budget: Duration,
+    draw: DrawProgress,
+    next: Option<SceneBuilder>,
+    transition: Option<Transition>,
+}
+
+impl LoadingScene {
+    pub fn new<F>(jobs: Vec<AssetJob>, next: F) -> Self
+    where
+        F: FnOnce() -> Box<dyn GameScene> + 'static,
+    {
+        Self {
+            progress: LoadingProgress {
+                total: jobs.len(),
+                ..Default::default()
+            },
+            jobs: jobs.into(),
+            current: None,
+            budget: Duration::from_millis(8),
+            draw: Box::new(draw_loading_progress),
+            next: Some(Box::new(next)),
+            transition: None,
+        }
+    }
+
+    /// Replace `draw_loading_progress()`.
+    pub fn on_draw<F>(mut self, draw: F) -> Self
+    where
+        F: FnMut(&LoadingProgress) + 'static,
+    {
+        self.draw = Box::new(draw);
+        self
+    }
+
+    pub fn budget(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Go to the next scene with a transition instead of cutting to it.
+    pub fn transition(mut self, transition: Transition) -> Self {
+        self.transition = Some(transition);
+        self
+    }
+
+    pub fn progress(&self) -> &LoadingProgress {
+        &self.progress
+    }
+
+    // Poll the current job once. It return false when the job is still waiting (e.g. for a http request).
+    fn poll_job(&mut self) -> bool {
+        if self.current.is_none() {
+            match self.jobs.pop_front() {
+                Some(job) => {
+                    self.progress.current = Some(job.label.clone());
+                    self.current = Some((job.start)());
+                }
+                None => return false,
+            }
+        }
+
+        // The scene polls again on every update, so it doesn't need to be woken up.
+        let mut context = Context::from_waker(Waker::noop());
+        let result = match self.current.as_mut() {
+            Some(job) => job.as_mut().poll(&mut context),
+            None => return false,
+        };
+
+        match result {
+            Poll::Ready(result) => {
+                match result {
+                    Ok(jobs) => {
+                        self.progress.total += jobs.len();
+                        for job in jobs.into_iter().rev() {
+                            self.jobs.push_front(job);
+                        }
+                    }
+                    Err(e) => {
+                        println!("LoadingScene: {}", e);
+                        self.progress.errors.push(e);
+                    }
+                }
+                self.progress.loaded += 1;
+                self.current = None;
+                // Keep a label on screen, the draw only happens after the update.
+                if let Some(job) = self.jobs.front() {
+                    self.progress.current = Some(job.label.clone());
+                }
+                true
+            }
+            Poll::Pending => false,
+        }
+    }
+}
+
+impl GameScene for LoadingScene {
+    fn init(&mut self) {}
+
+    fn update(&mut self, _time: &FrameTime) -> Result<SceneTransition, SceneError> {
+        let start = Instant::now();
+        while !self.progress.is_done() && self.poll_job() && start.elapsed() < self.budget {}
+
+        if !self.progress.is_done() {
+            return Ok(SceneTransition::None);
+        }
+
+        crate::app::check_strict_assets();
+
+        let next = match self.next.take() {
+            Some(builder) => builder(),
+            None => return Ok(SceneTransition::Pop),
+        };
+        match self.transition.take() {
+            Some(transition) => Ok(SceneTransition::ReplaceWith(next, transition)),
+            None => Ok(SceneTransition::Replace(next)),
+        }
+    }
+
+    fn draw(&mut self, _alpha: f32) {
+        (self.draw)(&self.progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EmptyScene;
+
+    impl GameScene for EmptyScene {
+        fn init(&mut self) {}
+
+        fn update(&mut self, _time: &FrameTime) -> Result<SceneTransition, SceneError> {
+            Ok(SceneTransition::None)
+        }
+
+        fn draw(&mut self, _alpha: f32) {}
+    }
+
+    fn job(label: &str) -> AssetJob {
+        AssetJob::new(label, || async { Ok(()) })
+    }
+
+    #[test]
+    fn jobs_from_a_job_run_next_and_keep_the_label() {
+        let list = AssetJob::with_jobs("list", || async { Ok(vec![job("a"), job("b")]) });
+        let mut scene =
+            LoadingScene::new(vec![list, job("c")], || Box::new(EmptyScene)).budget(Duration::ZERO);
+        let time = FrameTime::new(Duration::from_millis(16));
+
+        for (loaded, total, current) in [(1, 4, "a"), (2, 4, "b"), (3, 4, "c"), (4, 4, "c")] {
+            let transition = scene.update(&time).unwrap();
+            let progress = scene.progress();
+            assert_eq!((progress.loaded, progress.total), (loaded, total));
+            assert_eq!(progress.current.as_deref(), Some(current));
+            assert_eq!(
+                matches!(transition, SceneTransition::Replace(_)),
+                progress.is_done()
+            );
+        }
+    }
+}
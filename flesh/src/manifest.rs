@@ -0,0 +1,427 @@
+use crate::animation::{Animation, FrameEvent, PlaybackMode};
+use crate::aseprite::{AsepriteError, AsepriteSheet};
+use crate::file::load_string;
+use crate::textures::{load_image, GridSlice, TextureError};
+use macroquad::math::Rect;
+use macroquad::texture::Texture2D;
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+/// List of assets in a JSON file. Texture paths are relative to the manifest file.
+///
+/// ```json
+/// {
+///     "textures": [
+///         { "name": "hand-1", "path": "textures/1.png", "group": "player" }
+///     ],
+///     "sheets": [
+///         {
+///             "name": "items", "path": "textures/items.png",
+///             "grid": { "prefix": "item", "cell_width": 16, "cell_height": 16 },
+///             "regions": [{ "name": "logo", "x": 0, "y": 32, "w": 64, "h": 16 }]
+///         }
+///     ],
+///     "aseprite": [
+///         { "name": "hero", "path": "textures/hero.json" }
+///     ],
+///     "animations": [
+///         { "name": "player-idle", "frames": ["hand-1"], "frame_length_ms": 55, "mode": "ping_pong" }
+///     ]
+/// }
+/// ```
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AssetManifest {
+    #[serde(default)]
+    pub textures: Vec<TextureEntry>,
+    #[serde(default)]
+    pub sheets: Vec<SheetEntry>,
+    #[serde(default)]
+    pub aseprite: Vec<AsepriteEntry>,
+    #[serde(default)]
+    pub animations: Vec<AnimationEntry>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TextureEntry {
+    pub name: String,
+    pub path: String,
+    // See `TextureManager::set_group()`
+    pub group: Option<String>,
+}
+
+/// Sprite sheet texture. It is cut by `grid` and/or named `regions`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SheetEntry {
+    pub name: String,
+    pub path: String,
+    pub group: Option<String>,
+    pub grid: Option<GridEntry>,
+    #[serde(default)]
+    pub regions: Vec<RegionEntry>,
+}
+
+/// See `TextureManager::slice_grid()`
+#[derive(Deserialize, Debug, Clone)]
+pub struct GridEntry {
+    pub prefix: String,
+    pub cell_width: f32,
+    pub cell_height: f32,
+    #[serde(default)]
+    pub margin: f32,
+    #[serde(default)]
+    pub spacing: f32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RegionEntry {
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+/// Aseprite JSON data. See `AsepriteSheet::register()` for the animation names.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AsepriteEntry {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AnimationEntry {
+    pub name: String,
+    pub frames: Vec<String>,
+    // Use `animation::DEFAULT_FRAME_LENGTH` when it is not set
+    pub frame_length_ms: Option<u64>,
+    // Length of each frame. It overrides `frame_length_ms` for the frames it covers.
+    #[serde(default)]
+    pub frame_durations_ms: Vec<u64>,
+    #[serde(default)]
+    pub mode: PlaybackMode,
+    // e.g. [{ "frame": 3, "name": "footstep" }]
+    #[serde(default)]
+    pub events: Vec<FrameEvent>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ManifestError {
+    // The manifest file can't be read
+    Io { path: String, message: String },
+    // The manifest is not valid JSON or has wrong fields
+    Parse { message: String },
+    // The texture can't be loaded or added to `TextureManager`
+    Texture { name: String, error: TextureError },
+    // The Aseprite file can't be loaded
+    Aseprite { name: String, error: AsepriteError },
+    // The animation use a frame name that is not a loaded texture or region
+    UnknownFrame { animation: String, frame: String },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManifestError::Io { path, message } => {
+                write!(f, "can't read manifest: {}: {}", path, message)
+            }
+            ManifestError::Parse { message } => write!(f, "can't parse manifest: {}", message),
+            ManifestError::Texture { name, error } => {
+                write!(f, "can't load texture: {}: {}", name, error)
+            }
+            ManifestError::Aseprite { name, error } => {
+                write!(f, "can't load aseprite: {}: {}", name, error)
+            }
+            ManifestError::UnknownFrame { animation, frame } => {
+                write!(f, "animation: {}: unknown frame: {}", animation, frame)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+impl AssetManifest {
+    pub fn from_json(json: &str) -> Result<Self, ManifestError> {
+        serde_json::from_str(json).map_err(|e| ManifestError::Parse {
+            message: e.to_string(),
+        })
+    }
+
+    /// Read the manifest file. Texture paths are changed to be relative to the working directory.
+    pub async fn load(path: &str) -> Result<Self, ManifestError> {
+        let json = load_string(path).await.map_err(|e| ManifestError::Io {
+            path: path.to_owned(),
+            message: e.to_string(),
+        })?;
+
+        let mut manifest = Self::from_json(&json)?;
+        if let Some(directory) = Path::new(path).parent() {
+            for texture in manifest.textures.iter_mut() {
+                texture.path = directory.join(&texture.path).to_string_lossy().into_owned();
+            }
+            for sheet in manifest.sheets.iter_mut() {
+                sheet.path = directory.join(&sheet.path).to_string_lossy().into_owned();
+            }
+            for entry in manifest.aseprite.iter_mut() {
+                entry.path = directory.join(&entry.path).to_string_lossy().into_owned();
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// Load every texture into `crate::TEXTURE_MANAGER` and add every animation into `crate::ANIMATION_MANAGER`.
+    /// It keeps going after an error and return all of them.
+    pub async fn register(&self) -> Result<(), Vec<ManifestError>> {
+        self.register_with(false).await
+    }
+
+    /// Same as `register()`, but textures that are loaded already are replaced in place and keep their handle.
+    pub async fn reload(&self) -> Result<(), Vec<ManifestError>> {
+        self.register_with(true).await
+    }
+
+    async fn register_with(&self, reload: bool) -> Result<(), Vec<ManifestError>> {
+        let mut errors = vec![];
+
+        for texture in self.textures.iter() {
+            if let Err(error) = texture.register(reload).await {
+                errors.push(error);
+            }
+        }
+
+        for sheet in self.sheets.iter() {
+            if let Err(sheet_errors) = sheet.register(reload).await {
+                errors.extend(sheet_errors);
+            }
+        }
+
+        for entry in self.aseprite.iter() {
+            if let Err(error) = entry.register(reload).await {
+                errors.push(error);
+            }
+        }
+
+        if let Err(animation_errors) = self.register_animations() {
+            errors.extend(animation_errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Add every animation into `crate::ANIMATION_MANAGER`. Their frames must be loaded already.
+    pub(crate) fn register_animations(&self) -> Result<(), Vec<ManifestError>> {
+        let mut errors = vec![];
+
+        for entry in self.animations.iter() {
+            {
+                let texture_manager = crate::TEXTURE_MANAGER.lock().unwrap();
+                for frame in entry.frames.iter() {
+                    if texture_manager.get_frame(frame).is_none() {
+                        errors.push(ManifestError::UnknownFrame {
+                            animation: entry.name.clone(),
+                            frame: frame.clone(),
+                        });
+                    }
+                }
+            }
+
+            // `from_frames()` reports the missing frames. They are drawn with the placeholder unless strict mode is on.
+            let frame_names: Vec<&str> = entry.frames.iter().map(|v| v.as_str()).collect();
+            if let Ok(mut animation) = Animation::from_frames(&entry.name, frame_names) {
+                if let Some(frame_length) = entry.frame_length_ms {
+                    animation.frame_length = Duration::from_millis(frame_length);
+                }
+                for (frame, duration) in animation
+                    .frames
+                    .iter_mut()
+                    .zip(entry.frame_durations_ms.iter())
+                {
+                    frame.duration = Some(Duration::from_millis(*duration));
+                }
+                animation.mode = entry.mode;
+                animation.events = entry.events.clone();
+                crate::ANIMATION_MANAGER.lock().unwrap().insert(animation);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl TextureEntry {
+    pub(crate) async fn register(&self, reload: bool) -> Result<(), ManifestError> {
+        register_texture(&self.name, &self.path, &self.group, reload).await
+    }
+}
+
+impl SheetEntry {
+    /// Load the sheet texture, then cut it into regions.
+    pub(crate) async fn register(&self, reload: bool) -> Result<(), Vec<ManifestError>> {
+        register_texture(&self.name, &self.path, &self.group, reload)
+            .await
+            .map_err(|error| vec![error])?;
+
+        let mut errors = vec![];
+        let mut texture_manager = crate::TEXTURE_MANAGER.lock().unwrap();
+        if reload {
+            texture_manager.remove_regions(&self.name);
+        }
+        if let Some(grid) = self.grid.as_ref() {
+            let slice = GridSlice {
+                cell_width: grid.cell_width,
+                cell_height: grid.cell_height,
+                margin: grid.margin,
+                spacing: grid.spacing,
+            };
+            if let Err(error) = texture_manager.slice_grid(&self.name, &grid.prefix, slice) {
+                errors.push(ManifestError::Texture {
+                    name: self.name.clone(),
+                    error,
+                });
+            }
+        }
+
+        for region in self.regions.iter() {
+            let source = Rect::new(region.x, region.y, region.w, region.h);
+            if let Err(error) = texture_manager.add_region(&region.name, &self.name, source) {
+                errors.push(ManifestError::Texture {
+                    name: region.name.clone(),
+                    error,
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl AsepriteEntry {
+    pub(crate) async fn register(&self, reload: bool) -> Result<(), ManifestError> {
+        let result = match AsepriteSheet::load(&self.path).await {
+            Ok(sheet) => {
+                #[cfg(feature = "hot-reload")]
+                crate::hot_reload::watch_aseprite(&self.name, &self.path, &sheet);
+
+                if reload {
+                    sheet.reload(&self.name).await
+                } else {
+                    sheet.register(&self.name).await
+                }
+            }
+            Err(e) => Err(e),
+        };
+
+        result.map(|_| ()).map_err(|error| ManifestError::Aseprite {
+            name: self.name.clone(),
+            error,
+        })
+    }
+}
+
+pub(crate) async fn register_texture(
+    name: &str,
+    path: &str,
+    group: &Option<String>,
+    reload: bool,
+) -> Result<(), ManifestError> {
+    #[cfg(feature = "hot-reload")]
+    crate::hot_reload::watch(path, crate::hot_reload::Source::Texture(name.to_owned()));
+
+    // Don't keep TEXTURE_MANAGER locked while waiting for the file.
+    let result = match load_image(path).await {
+        Ok(image) => {
+            let mut texture_manager = crate::TEXTURE_MANAGER.lock().unwrap();
+            let texture = Texture2D::from_image(&image);
+            let result = if reload && texture_manager.get_texture_handle(name).is_some() {
+                texture_manager.replace(name, texture)
+            } else {
+                texture_manager.add(name, texture)
+            };
+            match result.as_ref() {
+                // Only the texture that was just added or replaced goes into the group.
+                Ok(_) => {
+                    if let Some(group) = group.as_ref() {
+                        texture_manager.set_group(name, group);
+                    }
+                }
+                // The manager didn't take it, free the upload.
+                Err(_) => texture.delete(),
+            }
+            result
+        }
+        Err(e) => {
+            crate::TEXTURE_MANAGER.lock().unwrap().report(e.clone());
+            Err(e)
+        }
+    };
+
+    match result {
+        Ok(handle) => {
+            println!("Loaded texture: {:?}, {}, {}", handle, name, path);
+            Ok(())
+        }
+        Err(error) => Err(ManifestError::Texture {
+            name: name.to_owned(),
+            error,
+        }),
+    }
+}
+
+/// Read the manifest file and register everything in it. See `AssetManifest`.
+pub async fn load_manifest(path: &str) -> Result<(), Vec<ManifestError>> {
+    let manifest = AssetManifest::load(path).await.map_err(|e| vec![e])?;
+
+    #[cfg(feature = "hot-reload")]
+    crate::hot_reload::watch(path, crate::hot_reload::Source::Manifest);
+
+    manifest.register().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_frames_use_the_placeholder() {
+        let manifest = AssetManifest::from_json(
+            r#"{ "animations": [{ "name": "missing-frames", "frames": ["missing-0", "missing-1"] }] }"#,
+        )
+        .unwrap();
+
+        let errors = manifest.register_animations().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(
+            matches!(&errors[0], ManifestError::UnknownFrame { frame, .. } if frame == "missing-0")
+        );
+
+        // Strict mode is off, the animation is registered and its frames don't resolve.
+        let animation_manager = crate::ANIMATION_MANAGER.lock().unwrap();
+        let animation = animation_manager.get("missing-frames").unwrap();
+        assert_eq!(animation.frames.len(), 2);
+
+        let texture_manager = crate::TEXTURE_MANAGER.lock().unwrap();
+        assert!(animation
+            .frames
+            .iter()
+            .all(|v| texture_manager.resolve(v).is_none()));
+        assert!(texture_manager
+            .errors()
+            .iter()
+            .any(|v| matches!(v, TextureError::UnknownName(name) if name == "missing-1")));
+    }
+}
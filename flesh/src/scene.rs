@@ -0,0 +1,98 @@
+use crate::time::FrameTime;
+use crate::transition::Transition;
+use macroquad::prelude::*;
+use std::any::Any;
+use std::fmt;
+
+/// None: It will keep using the same scene. Other than this. It mean changing to the new scene.
+pub enum SceneTransition {
+    // Dont change scene
+    None,
+    // Pop current scene from active stack
+    Pop,
+    // Push scene on active stack
+    Push(Box<dyn GameScene>),
+    // Replace current active scene on active stack
+    Replace(Box<dyn GameScene>),
+    // Replace current active scene after playing the transition effect
+    ReplaceWith(Box<dyn GameScene>, Transition),
+    // Exit every scene on the stack and close the application
+    Quit,
+}
+
+/// Error returned from `GameScene::update`. `SceneManager` handles it with its `SceneErrorPolicy`.
+#[derive(Debug, Clone)]
+pub struct SceneError {
+    // Name of the scene that failed
+    pub scene: String,
+    pub message: String,
+}
+
+impl SceneError {
+    pub fn new(scene: &str, message: &str) -> Self {
+        Self {
+            scene: scene.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.scene, self.message)
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// Let `SceneManager` give back the concrete scene type. It is implemented for every scene.
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub trait GameScene: AsAny {
+    fn init(&mut self);
+    fn update(&mut self, time: &FrameTime) -> Result<SceneTransition, SceneError>;
+    /// `alpha` (0.0 - 1.0) is how far the current frame is between the last fixed update and the next one.
+    fn draw(&mut self, alpha: f32);
+
+    /// Called when the scene becomes part of the active stack (Push, Replace or the first scene).
+    /// With `ReplaceWith` it is called when the transition starts, before the scene is first drawn.
+    fn on_enter(&mut self) {}
+
+    /// Called when the scene leaves the active stack (Pop or Replace). Good place for clean up.
+    fn on_exit(&mut self) {}
+
+    /// Called when another scene is pushed on top of this scene.
+    fn on_pause(&mut self) {}
+
+    /// Called when the scene on top of this scene is popped and this scene is active again.
+    fn on_resume(&mut self) {}
+
+    /// Called on the active scene when the quit key is pressed or the window is closing.
+    /// Return `false` to keep running, for example to show "Are you sure?" first.
+    fn on_quit_requested(&mut self) -> bool {
+        true
+    }
+
+    /// Transparent scene let the scenes beneath it be drawn first. (pause menu, dialog)
+    fn is_transparent(&self) -> bool {
+        false
+    }
+
+    /// Modal scene block the update of the scenes beneath it.
+    fn is_modal(&self) -> bool {
+        true
+    }
+}
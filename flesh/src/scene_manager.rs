@@ -0,0 +1,391 @@
+use crate::error_scene::ErrorScene;
+use crate::input;
+use crate::scene::{GameScene, SceneError, SceneTransition};
+use crate::time::FrameTime;
+use crate::transition::Transition;
+use macroquad::prelude::*;
+use std::time::Duration;
+
+struct ActiveTransition {
+    next: Box<dyn GameScene>,
+    transition: Transition,
+    elapsed: Duration,
+}
+
+/// What `SceneManager` does when a scene return `Err(SceneError)` from `update()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SceneErrorPolicy {
+    // Print the error and keep running the same scene
+    Log,
+    // Print the error and remove the failing scene from the stack
+    PopScene,
+    // Print the error and push `ErrorScene` that show the message
+    ShowErrorScene,
+    // Print the error and request the application to quit
+    Quit,
+}
+
+/// Keep the active scene stack. Call `update()` on every fixed update and `draw()` once per frame.
+pub struct SceneManager {
+    width: u32,
+    height: u32,
+    scenes: Vec<Box<dyn GameScene>>,
+    active_transition: Option<ActiveTransition>,
+    // Render targets for the old and the new scene. Created on the first transition.
+    canvases: Option<(Camera2D, Camera2D)>,
+    error_policy: SceneErrorPolicy,
+    quit_requested: bool,
+    quit_key: Option<KeyCode>,
+}
+
+impl SceneManager {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            scenes: vec![],
+            active_transition: None,
+            canvases: None,
+            error_policy: SceneErrorPolicy::Log,
+            quit_requested: false,
+            quit_key: Some(KeyCode::Escape),
+        }
+    }
+
+    /// Key that ask the active scene to quit. `None` mean only scenes can quit with `SceneTransition::Quit`.
+    pub fn set_quit_key(&mut self, key: Option<KeyCode>) {
+        self.quit_key = key;
+    }
+
+    /// Request to quit when the quit key was pressed. Call it once per rendered frame, not per fixed update,
+    /// so one press is seen once. `App` and `HeadlessRunner` call it.
+    pub fn check_quit_key(&mut self) {
+        if let Some(key) = self.quit_key {
+            if !self.quit_requested && input::is_key_pressed(key) {
+                self.request_quit();
+            }
+        }
+    }
+
+    /// Ask the active scene if the application can quit. The scene can veto it in `on_quit_requested()`.
+    pub fn request_quit(&mut self) {
+        let accepted = match self.scenes.last_mut() {
+            Some(active_scene) => active_scene.on_quit_requested(),
+            None => true,
+        };
+
+        if accepted {
+            self.quit();
+        }
+    }
+
+    /// Exit every scene from the top of the stack, so they can save their state.
+    fn quit(&mut self) {
+        // The incoming scene of a transition already entered.
+        if let Some(mut active) = self.active_transition.take() {
+            active.next.on_exit();
+        }
+        while let Some(mut scene) = self.scenes.pop() {
+            scene.on_exit();
+        }
+        self.quit_requested = true;
+    }
+
+    pub fn set_error_policy(&mut self, policy: SceneErrorPolicy) {
+        self.error_policy = policy;
+    }
+
+    pub fn error_policy(&self) -> SceneErrorPolicy {
+        self.error_policy
+    }
+
+    /// The main loop should stop when it is true.
+    pub fn is_quit_requested(&self) -> bool {
+        self.quit_requested
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// The active scene, if it is `T`.
+    pub fn top<T: GameScene + 'static>(&self) -> Option<&T> {
+        self.scenes
+            .last()
+            .and_then(|scene| scene.as_ref().as_any().downcast_ref::<T>())
+    }
+
+    pub fn top_mut<T: GameScene + 'static>(&mut self) -> Option<&mut T> {
+        self.scenes
+            .last_mut()
+            .and_then(|scene| scene.as_mut().as_any_mut().downcast_mut::<T>())
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.active_transition.is_some()
+    }
+
+    pub fn push(&mut self, mut scene: Box<dyn GameScene>) {
+        if let Some(active_scene) = self.scenes.last_mut() {
+            active_scene.on_pause();
+        }
+        scene.on_enter();
+        self.scenes.push(scene);
+    }
+
+    pub fn pop(&mut self) {
+        if let Some(mut old_scene) = self.scenes.pop() {
+            old_scene.on_exit();
+        }
+        release_unused_textures();
+        if let Some(active_scene) = self.scenes.last_mut() {
+            active_scene.on_resume();
+        }
+    }
+
+    pub fn replace(&mut self, mut scene: Box<dyn GameScene>) {
+        scene.on_enter();
+        self.replace_entered(scene);
+    }
+
+    // Replace the top scene with a scene that `on_enter()` was already called on.
+    fn replace_entered(&mut self, scene: Box<dyn GameScene>) {
+        if let Some(mut old_scene) = self.scenes.pop() {
+            old_scene.on_exit();
+        }
+        release_unused_textures();
+        self.scenes.push(scene);
+    }
+
+    pub fn update(&mut self, time: &FrameTime) {
+        if self.quit_requested {
+            return;
+        }
+
+        // Scenes are frozen while the transition is playing. The stack changes when it is done.
+        if let Some(active) = self.active_transition.as_mut() {
+            active.elapsed += time.delta;
+            if active.elapsed >= active.transition.duration {
+                if let Some(active) = self.active_transition.take() {
+                    self.replace_entered(active.next);
+                }
+            }
+            return;
+        }
+
+        // Scenes beneath a non-modal scene keep updating. Only the top scene can change the stack.
+        let top = self.scenes.len().saturating_sub(1);
+        let mut first_updated = top;
+        while first_updated > 0 && !self.scenes[first_updated].is_modal() {
+            first_updated -= 1;
+        }
+
+        for index in first_updated..top {
+            if let Err(e) = self.scenes[index].update(time) {
+                // The stack changed, the indices are not valid anymore.
+                if self.handle_error(index, e) {
+                    return;
+                }
+            }
+        }
+
+        if let Some(active_scene) = self.scenes.last_mut() {
+            match active_scene.update(time) {
+                Ok(v) => self.apply(v),
+                Err(e) => {
+                    self.handle_error(top, e);
+                }
+            }
+        }
+    }
+
+    // It return true when the policy changed the stack.
+    fn handle_error(&mut self, index: usize, error: SceneError) -> bool {
+        println!("SceneManager: update(): error: {}", error);
+
+        match self.error_policy {
+            SceneErrorPolicy::Log => return false,
+            SceneErrorPolicy::PopScene => {
+                if index + 1 == self.scenes.len() {
+                    self.pop();
+                } else if index < self.scenes.len() {
+                    self.scenes.remove(index).on_exit();
+                    release_unused_textures();
+                }
+            }
+            SceneErrorPolicy::ShowErrorScene => {
+                self.push(Box::new(ErrorScene::new(&error)));
+            }
+            SceneErrorPolicy::Quit => {
+                self.quit();
+            }
+        }
+        true
+    }
+
+    fn apply(&mut self, transition: SceneTransition) {
+        match transition {
+            SceneTransition::None => {}
+            SceneTransition::Push(s) => self.push(s),
+            SceneTransition::Pop => self.pop(),
+            SceneTransition::Replace(s) => self.replace(s),
+            SceneTransition::Quit => self.quit(),
+            SceneTransition::ReplaceWith(mut s, transition) => {
+                if transition.duration.is_zero() {
+                    self.replace(s);
+                } else {
+                    // The new scene is drawn during the transition, so it enters now.
+                    s.on_enter();
+                    self.active_transition = Some(ActiveTransition {
+                        next: s,
+                        transition,
+                        elapsed: Duration::ZERO,
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn draw(&mut self, alpha: f32) {
+        match self.active_transition.take() {
+            Some(mut active) => {
+                self.draw_transition(&mut active, alpha);
+                self.active_transition = Some(active);
+            }
+            None => {
+                let first_visible = first_visible_index(&self.scenes);
+                for scene in self.scenes.iter_mut().skip(first_visible) {
+                    scene.draw(alpha);
+                }
+            }
+        }
+    }
+
+    fn draw_transition(&mut self, active: &mut ActiveTransition, alpha: f32) {
+        let (width, height) = (self.width, self.height);
+        let (from_camera, to_camera) = self
+            .canvases
+            .get_or_insert_with(|| (canvas_camera(width, height), canvas_camera(width, height)));
+
+        // The old scene stack.
+        push_camera_state();
+        set_camera(from_camera);
+        let first_visible = first_visible_index(&self.scenes);
+        for scene in self.scenes.iter_mut().skip(first_visible) {
+            scene.draw(alpha);
+        }
+
+        // The stack after the top scene is replaced.
+        set_camera(to_camera);
+        let top = self.scenes.len().saturating_sub(1);
+        if active.next.is_transparent() {
+            let below = &mut self.scenes[..top];
+            let first_visible = first_visible_index(below);
+            for scene in below.iter_mut().skip(first_visible) {
+                scene.draw(alpha);
+            }
+        }
+        active.next.draw(alpha);
+        pop_camera_state();
+
+        let progress = active.elapsed.as_secs_f32() / active.transition.duration.as_secs_f32();
+        active.transition.draw(
+            progress,
+            from_camera.render_target.unwrap().texture,
+            to_camera.render_target.unwrap().texture,
+            width as f32,
+            height as f32,
+        );
+    }
+}
+
+/// Free the textures that only the removed scene was using.
+fn release_unused_textures() {
+    crate::TEXTURE_MANAGER.lock().unwrap().release_unused();
+}
+
+/// Start from the top scene and go down until we find the scene that is not transparent.
+fn first_visible_index(scenes: &[Box<dyn GameScene>]) -> usize {
+    let mut first_visible = scenes.len().saturating_sub(1);
+    while first_visible > 0 && scenes[first_visible].is_transparent() {
+        first_visible -= 1;
+    }
+    first_visible
+}
+
+fn canvas_camera(width: u32, height: u32) -> Camera2D {
+    let canvas = render_target(width, height);
+    canvas.texture.set_filter(FilterMode::Nearest);
+
+    Camera2D {
+        zoom: vec2(1. / width as f32 * 2., 1. / height as f32 * 2.),
+        target: vec2(width as f32 / 2., height as f32 / 2.),
+        render_target: Some(canvas),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::HeadlessRunner;
+
+    struct FailingScene;
+
+    impl GameScene for FailingScene {
+        fn init(&mut self) {}
+
+        fn update(&mut self, _time: &FrameTime) -> Result<SceneTransition, SceneError> {
+            Err(SceneError::new("FailingScene", "broken"))
+        }
+
+        fn draw(&mut self, _alpha: f32) {}
+    }
+
+    // Overlay that let the scenes beneath it update, e.g. a pause menu
+    struct OverlayScene {
+        updates: u32,
+    }
+
+    impl GameScene for OverlayScene {
+        fn init(&mut self) {}
+
+        fn update(&mut self, _time: &FrameTime) -> Result<SceneTransition, SceneError> {
+            self.updates += 1;
+            Ok(SceneTransition::None)
+        }
+
+        fn draw(&mut self, _alpha: f32) {}
+
+        fn is_modal(&self) -> bool {
+            false
+        }
+    }
+
+    fn run(policy: SceneErrorPolicy) -> HeadlessRunner {
+        let mut runner = HeadlessRunner::new(60);
+        runner.scene_manager_mut().set_error_policy(policy);
+        runner.push(Box::new(FailingScene));
+        runner.push(Box::new(OverlayScene { updates: 0 }));
+        runner.step(10);
+        runner
+    }
+
+    #[test]
+    fn logged_error_beneath_keeps_the_top_scene_updating() {
+        let runner = run(SceneErrorPolicy::Log);
+        let top = runner.scene_manager().top::<OverlayScene>().unwrap();
+        assert_eq!(top.updates, 10);
+    }
+
+    #[test]
+    fn popped_scene_beneath_is_removed() {
+        let mut runner = run(SceneErrorPolicy::PopScene);
+        let top = runner.scene_manager().top::<OverlayScene>().unwrap();
+        // The tick that removed it skips the other updates
+        assert_eq!(top.updates, 9);
+
+        runner.scene_manager_mut().pop();
+        assert!(runner.scene_manager().is_empty());
+    }
+}
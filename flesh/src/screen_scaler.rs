@@ -5,6 +5,8 @@ use macroquad::prelude::*;
 pub struct ScreenScaler {
     canvas: RenderTarget,
     camera: Camera2D,
+    inner_width: u32,
+    inner_height: u32,
 }
 
 impl ScreenScaler {
@@ -22,6 +24,8 @@ impl ScreenScaler {
         Self {
             canvas: canvas,
             camera: camera,
+            inner_width,
+            inner_height,
         }
     }
 
@@ -35,8 +39,8 @@ impl ScreenScaler {
         set_default_camera();
         clear_background(BLACK);
         let screen_rect = get_screen_rect(
-            crate::GAME_WIDTH as u32,
-            crate::GAME_HEIGHT as u32,
+            self.inner_width,
+            self.inner_height,
             screen_width() as u32,
             screen_height() as u32,
         );
@@ -0,0 +1,682 @@
+use crate::animation::{Animation, PlaybackMode};
+use crate::handle::Handle;
+use crate::time::FrameTime;
+use std::time::Duration;
+
+use macroquad::prelude::*;
+
+/// What happened during `Sprite::update()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpriteEvent {
+    // The sprite entered a frame with an event. See `Animation::events`.
+    Frame { name: String, frame: usize },
+    // A cycle ended and the animation plays again
+    Looped,
+    // The animation stopped. See `Sprite::is_end_of_animation()`.
+    Completed,
+}
+
+/// Fastest playback speed of a `Sprite`, forward or backward.
+pub const MAX_SPEED: f32 = 100.0;
+
+/// Draw static image or animation on scren.
+pub struct Sprite {
+    pause: bool,
+
+    is_loop: bool,
+
+    frame_index: usize,
+
+    animation: Animation,
+
+    frame_duration: Duration,
+
+    // Going back to the first frame in `PlaybackMode::PingPong`
+    backward: bool,
+
+    // Finished cycles since `play()`
+    play_count: u32,
+
+    // Events of the current `update()`
+    events: Vec<SpriteEvent>,
+
+    // The first frame was not entered yet, its events are sent on the next update
+    starting: bool,
+
+    // Playback speed multiplier. 0.0 freezes the sprite, negative values play it backward.
+    speed: f32,
+
+    // The animation stopped on the start of its cycle while playing backward
+    stopped_backward: bool,
+}
+
+impl Default for Sprite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sprite {
+    pub fn new() -> Self {
+        Self {
+            pause: false,
+            is_loop: true,
+            frame_index: 0,
+            animation: Animation::new(),
+            frame_duration: Duration::from_millis(0),
+            backward: false,
+            play_count: 0,
+            events: vec![],
+            starting: true,
+            speed: 1.0,
+            stopped_backward: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.is_loop = true;
+        self.speed = 1.0;
+        self.restart_animation();
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// e.g. 0.5 for slow motion, 0.0 to freeze, -1.0 to play backward. It is clamped to `MAX_SPEED`.
+    pub fn set_speed(&mut self, speed: f32) {
+        // NaN, infinity or a huge speed would overflow when scaling the frame time.
+        self.speed = if speed.is_finite() {
+            speed.clamp(-MAX_SPEED, MAX_SPEED)
+        } else {
+            0.0
+        };
+    }
+
+    pub fn frame_index(&self) -> usize {
+        self.frame_index
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.animation.frames.len()
+    }
+
+    pub fn get_current_animation_name(&self) -> &String {
+        &self.animation.name
+    }
+
+    pub fn is_loop(&self) -> bool {
+        self.is_loop
+    }
+
+    /// `PlaybackMode::Times` and `PlaybackMode::HoldLast` stop by themselves even when it loops.
+    pub fn set_loop(&mut self, is_loop: bool) {
+        self.is_loop = is_loop;
+    }
+
+    /// The animation stopped on its last frame (its first frame for `Reverse`/`PingPong`).
+    pub fn is_end_of_animation(&self) -> bool {
+        self.pause && self.play_count > 0
+    }
+
+    pub fn play_count(&self) -> u32 {
+        self.play_count
+    }
+
+    // Frame indices of one cycle in play order. Ping-pong goes back without repeating the ends.
+    fn cycle_order(&self) -> Vec<usize> {
+        let len = self.animation.frames.len();
+        match self.animation.mode {
+            PlaybackMode::Reverse => (0..len).rev().collect(),
+            PlaybackMode::PingPong => (0..len).chain((1..len.saturating_sub(1)).rev()).collect(),
+            _ => (0..len).collect(),
+        }
+    }
+
+    // Position of the current frame in `cycle_order()`
+    fn cycle_position(&self) -> usize {
+        let last = self.animation.frames.len().saturating_sub(1);
+        match self.animation.mode {
+            PlaybackMode::Reverse => last - self.frame_index.min(last),
+            PlaybackMode::PingPong if self.backward => last + (last - self.frame_index),
+            _ => self.frame_index,
+        }
+    }
+
+    /// Time in the current cycle, from 0.0 to 1.0.
+    pub fn normalized_time(&self) -> f32 {
+        let order = self.cycle_order();
+        let total: Duration = order
+            .iter()
+            .map(|i| self.animation.frame_length_at(*i))
+            .sum();
+        if total.is_zero() {
+            return 0.0;
+        }
+
+        let position = self.cycle_position();
+        let current = self.animation.frame_length_at(self.frame_index);
+        let before: Duration = order
+            .iter()
+            .take(position)
+            .map(|i| self.animation.frame_length_at(*i))
+            .sum();
+        let elapsed = before + self.frame_duration.min(current);
+        (elapsed.as_secs_f32() / total.as_secs_f32()).clamp(0.0, 1.0)
+    }
+
+    /// Jump to the time in the current cycle, from 0.0 to 1.0. Frame events are not sent.
+    /// It plays again when the animation was finished.
+    pub fn seek_normalized(&mut self, time: f32) {
+        let order = self.cycle_order();
+        let total: Duration = order
+            .iter()
+            .map(|i| self.animation.frame_length_at(*i))
+            .sum();
+        if order.is_empty() {
+            return;
+        }
+
+        let mut remaining = total.mul_f32(time.clamp(0.0, 1.0));
+        let last = self.animation.frames.len() - 1;
+        for (position, index) in order.iter().enumerate() {
+            let length = self.animation.frame_length_at(*index);
+            if remaining < length || position == order.len() - 1 {
+                self.frame_index = *index;
+                self.backward = self.animation.mode == PlaybackMode::PingPong && position > last;
+                self.frame_duration = remaining.min(length);
+                self.starting = false;
+                self.pause = false;
+                return;
+            }
+            remaining -= length;
+        }
+    }
+
+    /// Jump to the start of the frame. Frame events are not sent.
+    /// It plays again when the animation was finished.
+    pub fn seek(&mut self, frame: usize) {
+        if self.animation.frames.is_empty() {
+            return;
+        }
+
+        self.frame_index = frame.min(self.animation.frames.len() - 1);
+        self.frame_duration = Duration::from_millis(0);
+        self.backward = false;
+        self.starting = false;
+        self.pause = false;
+    }
+
+    // First frame for the playback mode
+    fn start_index(&self) -> usize {
+        match self.animation.mode {
+            PlaybackMode::Reverse => self.animation.frames.len().saturating_sub(1),
+            _ => 0,
+        }
+    }
+
+    // Play animation
+    pub fn play(&mut self, new_animation: &Animation) {
+        self.animation = new_animation.clone();
+        self.restart_animation();
+    }
+
+    /// Play animation from `crate::ANIMATION_MANAGER`. It return false when the handle is stale.
+    pub fn play_handle(&mut self, handle: Handle<Animation>) -> bool {
+        let animation_manager = crate::ANIMATION_MANAGER.lock().unwrap();
+        match animation_manager.get_by_handle(handle) {
+            Some(animation) => {
+                self.play(animation);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // restart the same animation from first frame
+    pub fn restart_animation(&mut self) {
+        self.frame_duration = Duration::from_millis(0);
+        self.frame_index = self.start_index();
+        self.pause = false;
+        self.backward = false;
+        self.play_count = 0;
+        self.starting = true;
+    }
+
+    /// internal function for changing current frame of the animation.
+    /// Each frame can have its own length. See `Animation::frame_length_at()`.
+    fn advance(&mut self, delta: Duration) -> bool {
+        match self.frame_duration.checked_add(delta) {
+            Some(v) => self.frame_duration = v,
+            None => self.frame_duration = Duration::from_millis(0),
+        };
+
+        if self.pause || self.animation.frames.is_empty() {
+            return false;
+        }
+
+        if self.starting {
+            self.starting = false;
+            self.enter(self.frame_index);
+        }
+
+        let mut is_end = false;
+        loop {
+            let frame_length = self.animation.frame_length_at(self.frame_index);
+            if frame_length.is_zero() || self.frame_duration < frame_length {
+                break;
+            }
+
+            self.frame_duration -= frame_length;
+            if self.step() {
+                is_end = true;
+                if self.pause {
+                    break;
+                }
+            }
+        }
+
+        is_end
+    }
+
+    // Show the frame and queue its events.
+    fn enter(&mut self, index: usize) {
+        self.frame_index = index;
+        for event in self.animation.events_at(index) {
+            self.events.push(SpriteEvent::Frame {
+                name: event.name.clone(),
+                frame: index,
+            });
+        }
+    }
+
+    /// Go to the next frame of the playback mode. It return true when a cycle ends.
+    fn step(&mut self) -> bool {
+        let last = self.animation.frames.len() - 1;
+        match self.animation.mode {
+            PlaybackMode::Forward | PlaybackMode::Times(_) | PlaybackMode::HoldLast => {
+                if self.frame_index < last {
+                    self.enter(self.frame_index + 1);
+                    return false;
+                }
+                self.finish_cycle(0, last)
+            }
+            PlaybackMode::Reverse => {
+                if self.frame_index > 0 {
+                    self.enter(self.frame_index - 1);
+                    return false;
+                }
+                self.finish_cycle(last, 0)
+            }
+            PlaybackMode::PingPong => {
+                if !self.backward && self.frame_index < last {
+                    self.enter(self.frame_index + 1);
+                    return false;
+                }
+
+                self.backward = true;
+                if self.frame_index > 1 {
+                    self.enter(self.frame_index - 1);
+                    return false;
+                }
+
+                // Back on the first frame. The next cycle goes forward from it.
+                self.backward = false;
+                self.finish_cycle(0, 0)
+            }
+        }
+    }
+
+    // Count the finished cycle and tell if the mode plays another one.
+    fn plays_again(&mut self) -> bool {
+        self.play_count += 1;
+        match self.animation.mode {
+            PlaybackMode::Times(times) => self.play_count < times,
+            PlaybackMode::HoldLast => false,
+            _ => self.is_loop,
+        }
+    }
+
+    // Restart from `restart_index`, or stop on `stop_index` when the mode doesn't play again.
+    fn finish_cycle(&mut self, restart_index: usize, stop_index: usize) -> bool {
+        if self.plays_again() {
+            self.events.push(SpriteEvent::Looped);
+            self.enter(restart_index);
+        } else {
+            if self.frame_index != stop_index {
+                self.enter(stop_index);
+            }
+            self.pause = true;
+            self.stopped_backward = false;
+            self.events.push(SpriteEvent::Completed);
+        }
+        true
+    }
+
+    /// internal function for playing backward: the time goes back through the cycle.
+    fn rewind(&mut self, delta: Duration) -> bool {
+        // A finished animation goes back from the start of the frame it stopped on.
+        if self.is_end_of_animation() && !self.stopped_backward {
+            self.pause = false;
+            self.frame_duration = Duration::from_millis(0);
+            // Ping-pong stops on the first frame, at the end of its cycle.
+            self.backward = self.animation.mode == PlaybackMode::PingPong;
+        }
+
+        if self.pause || self.animation.frames.is_empty() {
+            return false;
+        }
+
+        if self.starting {
+            self.starting = false;
+            self.enter(self.frame_index);
+        }
+
+        let mut is_end = false;
+        let mut remaining = delta;
+        loop {
+            if remaining <= self.frame_duration {
+                self.frame_duration -= remaining;
+                break;
+            }
+
+            // Go back to the end of the previous frame
+            remaining -= self.frame_duration;
+            self.frame_duration = Duration::from_millis(0);
+            if self.step_back() {
+                is_end = true;
+                if self.pause {
+                    break;
+                }
+            }
+
+            self.frame_duration = self.animation.frame_length_at(self.frame_index);
+            if self.frame_duration.is_zero() {
+                break;
+            }
+        }
+
+        is_end
+    }
+
+    // Go to the previous frame of the cycle. It return true when it passes the start of the cycle.
+    fn step_back(&mut self) -> bool {
+        let order = self.cycle_order();
+        let position = self.cycle_position();
+        let last = self.animation.frames.len() - 1;
+
+        let position = if position > 0 {
+            position - 1
+        } else if self.plays_again() {
+            self.events.push(SpriteEvent::Looped);
+            order.len() - 1
+        } else {
+            self.pause = true;
+            self.stopped_backward = true;
+            self.events.push(SpriteEvent::Completed);
+            return true;
+        };
+
+        self.enter(order[position]);
+        self.backward = self.animation.mode == PlaybackMode::PingPong && position > last;
+        position == order.len() - 1
+    }
+
+    /// It return true when the animation finished a cycle. `events()` has what happened during the update.
+    /// The time is scaled by `speed()`.
+    pub fn update(&mut self, time: &FrameTime) -> bool {
+        self.events.clear();
+
+        #[cfg(feature = "hot-reload")]
+        self.refresh_animation();
+
+        // Scaling goes through floats and can be off by a nanosecond, so don't scale at normal speed.
+        let delta = if self.speed.abs() == 1.0 {
+            time.delta
+        } else {
+            time.delta.mul_f64(self.speed.abs() as f64)
+        };
+        if self.speed < 0.0 {
+            self.rewind(delta)
+        } else {
+            self.advance(delta)
+        }
+    }
+
+    /// Events of the last `update()`, in the order they happened.
+    /// Every frame crossed in one update is included, even when it was not drawn.
+    pub fn events(&self) -> &[SpriteEvent] {
+        &self.events
+    }
+
+    /// Take the new version of the animation after it was reloaded.
+    #[cfg(feature = "hot-reload")]
+    fn refresh_animation(&mut self) {
+        // The caller may hold the lock already. Try again on the next update.
+        let animation_manager = match crate::ANIMATION_MANAGER.try_lock() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        if let Some(animation) = animation_manager.get(&self.animation.name) {
+            if animation.revision() != self.animation.revision() {
+                self.animation = animation.clone();
+                if self.frame_index >= self.animation.frames.len() {
+                    self.frame_index = 0;
+                }
+            }
+        }
+    }
+
+    // draw on screen at position and rotation. (-1.0, 1.0)
+    pub fn draw(&self, position: Vec2, rotation: f32) {
+        let frame_index = self.frame_index;
+        match self.animation.frames.get(frame_index) {
+            Some(frame) => {
+                // A missing texture is drawn as a checkerboard in the size of the frame.
+                let (texture, source) = crate::TEXTURE_MANAGER
+                    .lock()
+                    .unwrap()
+                    .resolve_or_placeholder(frame);
+                let size = match frame.source.or(source) {
+                    Some(source) => vec2(source.w, source.h),
+                    None => vec2(texture.width(), texture.height()),
+                };
+
+                draw_texture_ex(
+                    texture,
+                    position.x - size.x / 2.0,
+                    position.y - size.y / 2.0,
+                    WHITE,
+                    DrawTextureParams {
+                        rotation: (rotation * 360.0).to_radians(),
+                        source,
+                        dest_size: Some(size),
+                        ..Default::default()
+                    },
+                );
+            }
+            None => {
+                // println!("Sprite: Animation:  can't find texture handle from index: {:?} {}", self.animation.frames, frame_index);
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{test_animation, TEST_FRAME_LENGTH};
+
+    fn sprite(frame_count: usize) -> Sprite {
+        sprite_with_mode(frame_count, PlaybackMode::Forward)
+    }
+
+    // Frame index after each of `updates` frame lengths.
+    fn frame_indices(sprite: &mut Sprite, updates: usize) -> Vec<usize> {
+        (0..updates)
+            .map(|_| {
+                sprite.update(&FrameTime::new(TEST_FRAME_LENGTH));
+                sprite.frame_index()
+            })
+            .collect()
+    }
+
+    fn sprite_with_mode(frame_count: usize, mode: PlaybackMode) -> Sprite {
+        let mut sprite = Sprite::new();
+        sprite.play(&test_animation("test", frame_count).with_mode(mode));
+        sprite
+    }
+
+    #[test]
+    fn forward_loops_to_the_first_frame() {
+        let mut sprite = sprite(3);
+        assert_eq!(frame_indices(&mut sprite, 5), vec![1, 2, 0, 1, 2]);
+        assert!(!sprite.is_end_of_animation());
+    }
+
+    #[test]
+    fn reverse_starts_on_the_last_frame() {
+        let mut sprite = sprite_with_mode(4, PlaybackMode::Reverse);
+        assert_eq!(sprite.frame_index(), 3);
+        assert_eq!(frame_indices(&mut sprite, 5), vec![2, 1, 0, 3, 2]);
+    }
+
+    #[test]
+    fn ping_pong_doesnt_repeat_the_ends() {
+        let mut sprite = sprite_with_mode(4, PlaybackMode::PingPong);
+        assert_eq!(frame_indices(&mut sprite, 8), vec![1, 2, 3, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn times_stops_on_the_last_frame() {
+        let mut sprite = sprite_with_mode(3, PlaybackMode::Times(2));
+        assert_eq!(frame_indices(&mut sprite, 4), vec![1, 2, 0, 1]);
+        assert!(!sprite.is_end_of_animation());
+
+        assert_eq!(frame_indices(&mut sprite, 3), vec![2, 2, 2]);
+        assert!(sprite.is_end_of_animation());
+        assert_eq!(sprite.play_count(), 2);
+    }
+
+    #[test]
+    fn hold_last_stops_after_one_cycle_even_when_looping() {
+        let mut sprite = sprite_with_mode(3, PlaybackMode::HoldLast);
+        assert!(sprite.is_loop());
+        assert_eq!(frame_indices(&mut sprite, 4), vec![1, 2, 2, 2]);
+        assert!(sprite.is_end_of_animation());
+        assert_eq!(sprite.play_count(), 1);
+    }
+
+    fn sprite_with_events(is_loop: bool) -> Sprite {
+        let mut animation = test_animation("test", 4);
+        for frame in 0..4 {
+            animation = animation.with_event(frame, &format!("e{}", frame));
+        }
+        let mut sprite = Sprite::new();
+        sprite.play(&animation);
+        sprite.set_loop(is_loop);
+        sprite
+    }
+
+    fn frame_event(name: &str, frame: usize) -> SpriteEvent {
+        SpriteEvent::Frame {
+            name: name.to_owned(),
+            frame,
+        }
+    }
+
+    #[test]
+    fn one_update_queues_every_crossed_frame() {
+        let mut sprite = sprite_with_events(false);
+        sprite.update(&FrameTime::new(Duration::from_millis(350)));
+        assert_eq!(
+            sprite.events(),
+            &[
+                frame_event("e0", 0),
+                frame_event("e1", 1),
+                frame_event("e2", 2),
+                frame_event("e3", 3),
+            ]
+        );
+
+        assert!(sprite.update(&FrameTime::new(TEST_FRAME_LENGTH)));
+        assert_eq!(sprite.events(), &[SpriteEvent::Completed]);
+        assert!(sprite.is_end_of_animation());
+
+        // The queue is cleared on every update.
+        sprite.update(&FrameTime::new(TEST_FRAME_LENGTH));
+        assert!(sprite.events().is_empty());
+    }
+
+    #[test]
+    fn looping_queues_looped_then_the_first_frame() {
+        let mut sprite = sprite_with_events(true);
+        sprite.update(&FrameTime::new(Duration::from_millis(350)));
+        assert_eq!(sprite.events().len(), 4);
+
+        assert!(sprite.update(&FrameTime::new(TEST_FRAME_LENGTH)));
+        assert_eq!(
+            sprite.events(),
+            &[SpriteEvent::Looped, frame_event("e0", 0)]
+        );
+        assert_eq!(sprite.play_count(), 1);
+        assert!(!sprite.is_end_of_animation());
+    }
+
+    #[test]
+    fn finished_animation_plays_backward() {
+        let mut sprite = sprite(4);
+        sprite.set_loop(false);
+        assert_eq!(frame_indices(&mut sprite, 5), vec![1, 2, 3, 3, 3]);
+        assert!(sprite.is_end_of_animation());
+
+        sprite.set_speed(-1.0);
+        assert_eq!(frame_indices(&mut sprite, 3), vec![2, 1, 0]);
+        assert!(!sprite.is_end_of_animation());
+
+        // It stops again on the first frame and stays there.
+        assert!(sprite.update(&FrameTime::new(TEST_FRAME_LENGTH)));
+        assert_eq!(sprite.events(), &[SpriteEvent::Completed]);
+        assert_eq!(frame_indices(&mut sprite, 2), vec![0, 0]);
+        assert!(sprite.events().is_empty());
+    }
+
+    #[test]
+    fn finished_ping_pong_plays_backward() {
+        let mut sprite = sprite_with_mode(3, PlaybackMode::PingPong);
+        sprite.set_loop(false);
+        assert_eq!(frame_indices(&mut sprite, 4), vec![1, 2, 1, 0]);
+        assert!(sprite.is_end_of_animation());
+
+        sprite.set_speed(-1.0);
+        assert_eq!(frame_indices(&mut sprite, 3), vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn speed_is_clamped() {
+        let mut sprite = sprite(4);
+        for (speed, expected) in [
+            (1e30, MAX_SPEED),
+            (-1e30, -MAX_SPEED),
+            (f32::NAN, 0.0),
+            (f32::INFINITY, 0.0),
+            (0.5, 0.5),
+        ] {
+            sprite.set_speed(speed);
+            assert_eq!(sprite.speed(), expected);
+            sprite.update(&FrameTime::new(TEST_FRAME_LENGTH));
+        }
+    }
+
+    #[test]
+    fn backward_steps_one_frame_per_frame_length() {
+        let mut sprite = sprite(4);
+        sprite.seek(3);
+        sprite.set_speed(-1.0);
+        for expected in [2, 1, 0] {
+            sprite.update(&FrameTime::new(TEST_FRAME_LENGTH));
+            assert_eq!(sprite.frame_index(), expected);
+        }
+    }
+}
@@ -0,0 +1,805 @@
+use crate::animation::Frame;
+use crate::atlas::{Atlas, AtlasSettings, AtlasSlot};
+use crate::file::load_file;
+use crate::handle::{Assets, Handle};
+use macroquad::color::{BLACK, MAGENTA};
+use macroquad::math::Rect;
+use macroquad::texture::{FilterMode, Image, Texture2D};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureError {
+    // The file can't be read
+    FileNotFound { path: String, message: String },
+    // The file is read but it is not a supported image
+    DecodeFailed { path: String, message: String },
+    // The name is already used by another texture
+    DuplicateName(String),
+    // No texture or sprite sheet region with this name
+    UnknownName(String),
+    // The slice settings or the region rect don't fit the sprite sheet
+    InvalidRegion { name: String, message: String },
+    // The image is bigger than an atlas page
+    AtlasFull { name: String, message: String },
+    // The atlas page size or padding can't be used
+    InvalidAtlasSettings(String),
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::FileNotFound { path, message } => {
+                write!(f, "file not found: {}: {}", path, message)
+            }
+            TextureError::DecodeFailed { path, message } => {
+                write!(f, "decode failed: {}: {}", path, message)
+            }
+            TextureError::DuplicateName(name) => write!(f, "duplicate texture name: {}", name),
+            TextureError::UnknownName(name) => write!(f, "unknown texture name: {}", name),
+            TextureError::InvalidRegion { name, message } => {
+                write!(f, "invalid region: {}: {}", name, message)
+            }
+            TextureError::AtlasFull { name, message } => {
+                write!(f, "can't pack into atlas: {}: {}", name, message)
+            }
+            TextureError::InvalidAtlasSettings(message) => {
+                write!(f, "invalid atlas settings: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+/// Decode png/tga bytes into RGBA8 `Image`. It does not need the GPU.
+pub fn decode_image(path: &str, bytes: &[u8]) -> Result<Image, TextureError> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| TextureError::DecodeFailed {
+            path: path.to_owned(),
+            message: e.to_string(),
+        })?
+        .to_rgba8();
+
+    Ok(Image {
+        width: image.width() as u16,
+        height: image.height() as u16,
+        bytes: image.into_raw(),
+    })
+}
+
+// Size of the placeholder texture and of its squares, in pixels
+const PLACEHOLDER_SIZE: u16 = 32;
+const PLACEHOLDER_CELL: u16 = 8;
+
+/// Magenta/black checkerboard drawn in place of missing textures. It does not need the GPU.
+pub fn placeholder_image() -> Image {
+    let mut image = Image::gen_image_color(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, BLACK);
+    for y in 0..PLACEHOLDER_SIZE {
+        for x in 0..PLACEHOLDER_SIZE {
+            if (x / PLACEHOLDER_CELL) % 2 == (y / PLACEHOLDER_CELL) % 2 {
+                image.set_pixel(x as u32, y as u32, MAGENTA);
+            }
+        }
+    }
+    image
+}
+
+/// Read and decode the image file without touching `TextureManager`.
+pub async fn load_image(path: &str) -> Result<Image, TextureError> {
+    let bytes = load_file(path)
+        .await
+        .map_err(|e| TextureError::FileNotFound {
+            path: path.to_owned(),
+            message: e.to_string(),
+        })?;
+
+    decode_image(path, &bytes)
+}
+
+/// Strong reference that keep a texture loaded. `Animation` keep one for every frame.
+/// See `TextureManager::release_unused()`.
+#[derive(Clone, Debug)]
+pub struct TextureRef {
+    handle: Handle<Texture2D>,
+    _count: Arc<()>,
+}
+
+impl TextureRef {
+    pub fn handle(&self) -> Handle<Texture2D> {
+        self.handle
+    }
+}
+
+/// Uniform grid for `TextureManager::slice_grid()`. Values are in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GridSlice {
+    pub cell_width: f32,
+    pub cell_height: f32,
+    // Space between the sheet border and the first cells
+    pub margin: f32,
+    // Space between two cells
+    pub spacing: f32,
+}
+
+impl GridSlice {
+    pub fn new(cell_width: f32, cell_height: f32) -> Self {
+        Self {
+            cell_width,
+            cell_height,
+            margin: 0.0,
+            spacing: 0.0,
+        }
+    }
+
+    /// The cell size must be positive. Margin and spacing can't be negative.
+    pub fn is_valid(&self) -> bool {
+        self.cell_width > 0.0 && self.cell_height > 0.0 && self.margin >= 0.0 && self.spacing >= 0.0
+    }
+
+    /// Cell rects from left to right, then top to bottom. Empty when the grid is not valid.
+    pub fn rects(&self, sheet_width: f32, sheet_height: f32) -> Vec<Rect> {
+        let mut rects = vec![];
+        if !self.is_valid() {
+            return rects;
+        }
+
+        let mut y = self.margin;
+        while y + self.cell_height <= sheet_height - self.margin {
+            let mut x = self.margin;
+            while x + self.cell_width <= sheet_width - self.margin {
+                rects.push(Rect::new(x, y, self.cell_width, self.cell_height));
+                x += self.cell_width + self.spacing;
+            }
+            y += self.cell_height + self.spacing;
+        }
+        rects
+    }
+}
+
+struct TextureInfo {
+    name: String,
+    group: Option<String>,
+    // Size in bytes on the GPU (RGBA8)
+    size: usize,
+    count: Arc<()>,
+    // Set on the first `acquire()`. Only these textures are freed by `release_unused()`.
+    ref_counted: bool,
+}
+
+pub struct TextureManager {
+    textures: Assets<Texture2D>,
+    names: HashMap<String, Handle<Texture2D>>,
+    infos: HashMap<Handle<Texture2D>, TextureInfo>,
+    // Named sub-rects of sprite sheets
+    regions: HashMap<String, Frame>,
+    // Textures that were packed into an atlas page. Their handle now points to the page.
+    atlas: HashMap<Handle<Texture2D>, AtlasSlot>,
+    // Fail the startup when any asset can't be loaded. See `App::strict_assets()`.
+    strict: bool,
+    errors: Vec<TextureError>,
+    // Created on first use, the GPU is not ready when the manager is created
+    placeholder: Option<Texture2D>,
+    // Missing names/handles that were warned about already
+    missing: HashSet<String>,
+}
+
+impl Default for TextureManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextureManager {
+    pub fn new() -> Self {
+        Self {
+            textures: Assets::new(),
+            names: HashMap::new(),
+            infos: HashMap::new(),
+            regions: HashMap::new(),
+            atlas: HashMap::new(),
+            strict: false,
+            errors: vec![],
+            placeholder: None,
+            missing: HashSet::new(),
+        }
+    }
+
+    // Load texture from file and keep Texture2D. It can retrieve using Handle<Texture2D>
+    pub async fn load_from_file(
+        &mut self,
+        name: &str,
+        path: &str,
+    ) -> Result<Handle<Texture2D>, TextureError> {
+        if self.names.contains_key(name) {
+            let error = TextureError::DuplicateName(name.to_owned());
+            self.report(error.clone());
+            return Err(error);
+        }
+
+        #[cfg(feature = "hot-reload")]
+        crate::hot_reload::watch(path, crate::hot_reload::Source::Texture(name.to_owned()));
+
+        match load_image(path).await {
+            Ok(image) => {
+                let handle = self.add(name, Texture2D::from_image(&image))?;
+
+                println!("Loaded texture: {:?}, {}, {}", handle, name, path);
+                Ok(handle)
+            }
+            Err(e) => {
+                self.report(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Keep texture that is already loaded or created. It return the new handle.
+    pub fn add(
+        &mut self,
+        name: &str,
+        texture: Texture2D,
+    ) -> Result<Handle<Texture2D>, TextureError> {
+        if self.is_name_used(name) {
+            let error = TextureError::DuplicateName(name.to_owned());
+            self.report(error.clone());
+            return Err(error);
+        }
+
+        let size = texture.width() as usize * texture.height() as usize * 4;
+        let handle = self.textures.insert(texture);
+        self.names.insert(name.to_owned(), handle);
+        self.infos.insert(
+            handle,
+            TextureInfo {
+                name: name.to_owned(),
+                group: None,
+                size,
+                count: Arc::new(()),
+                ref_counted: false,
+            },
+        );
+
+        Ok(handle)
+    }
+
+    /// Swap the texture behind the name and keep its handle, so `Sprite`s draw the new one.
+    /// Regions of it stay. A packed texture leaves its atlas page.
+    pub fn replace(
+        &mut self,
+        name: &str,
+        texture: Texture2D,
+    ) -> Result<Handle<Texture2D>, TextureError> {
+        let handle = match self.get_texture_handle(name) {
+            Some(v) => v,
+            None => {
+                let error = TextureError::UnknownName(name.to_owned());
+                self.report(error.clone());
+                return Err(error);
+            }
+        };
+
+        let packed = self.atlas.remove(&handle).is_some();
+        if let Some(old) = self.textures.get_mut(handle) {
+            if !packed {
+                old.delete();
+            }
+            *old = texture;
+        }
+        if let Some(info) = self.infos.get_mut(&handle) {
+            info.size = texture.width() as usize * texture.height() as usize * 4;
+        }
+
+        Ok(handle)
+    }
+
+    /// Free the texture even when it is still used. Handles to it resolve to `None` after this.
+    /// Removing an atlas page also removes the textures packed into it.
+    pub fn remove(&mut self, handle: Handle<Texture2D>) -> bool {
+        match self.textures.remove(handle) {
+            Some(texture) => {
+                if let Some(info) = self.infos.remove(&handle) {
+                    self.names.remove(&info.name);
+                }
+                self.regions.retain(|_, frame| frame.texture != handle);
+
+                // The page is shared, it is deleted with the page handle only.
+                if self.atlas.remove(&handle).is_none() {
+                    texture.delete();
+
+                    let packed: Vec<Handle<Texture2D>> = self
+                        .atlas
+                        .iter()
+                        .filter(|(_, slot)| slot.page == handle)
+                        .map(|(packed, _)| *packed)
+                        .collect();
+                    for packed in packed {
+                        self.remove(packed);
+                    }
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pack loaded textures into atlas pages. See `add_atlas()`.
+    /// It reads the pixels back from the GPU. Use `Atlas::build()` and `add_atlas()` with the decoded
+    /// images to skip it.
+    pub fn pack_atlas(
+        &mut self,
+        names: &[&str],
+        settings: AtlasSettings,
+    ) -> Result<Vec<Handle<Texture2D>>, TextureError> {
+        let mut images = vec![];
+        for name in names.iter() {
+            let handle = match self.get_texture_handle(name) {
+                Some(v) => v,
+                None => {
+                    let error = TextureError::UnknownName((*name).to_owned());
+                    self.report(error.clone());
+                    return Err(error);
+                }
+            };
+
+            // Already in a page
+            if self.atlas.contains_key(&handle) {
+                continue;
+            }
+
+            if let Some(texture) = self.textures.get(handle) {
+                images.push(((*name).to_owned(), texture.get_texture_data()));
+            }
+        }
+
+        let atlas = match Atlas::build(&images, &settings) {
+            Ok(v) => v,
+            Err(e) => {
+                self.report(e.clone());
+                return Err(e);
+            }
+        };
+        self.add_atlas(&atlas)
+    }
+
+    /// Upload the atlas pages as "atlas-0", "atlas-1", ... and return their handles.
+    /// A loaded texture with the same name as an entry is freed and its handle is remapped to the page,
+    /// so existing `Animation`s keep working. Other entries are added as regions.
+    pub fn add_atlas(&mut self, atlas: &Atlas) -> Result<Vec<Handle<Texture2D>>, TextureError> {
+        let mut pages = vec![];
+        let mut page_index = 0;
+        for image in atlas.pages.iter() {
+            while self.is_name_used(&format!("atlas-{}", page_index)) {
+                page_index += 1;
+            }
+            let name = format!("atlas-{}", page_index);
+            pages.push(self.add(&name, Texture2D::from_image(image))?);
+        }
+
+        for entry in atlas.entries.iter() {
+            let page = pages[entry.page];
+            let page_texture = *self.textures.get(page).unwrap();
+
+            match self.get_texture_handle(&entry.name) {
+                Some(handle) => {
+                    let packed = self.atlas.contains_key(&handle);
+                    if let Some(texture) = self.textures.get_mut(handle) {
+                        // A texture that was packed before points to another page already
+                        if !packed {
+                            texture.delete();
+                        }
+                        *texture = page_texture;
+                    }
+                    if let Some(info) = self.infos.get_mut(&handle) {
+                        // The page is counted instead
+                        info.size = 0;
+                    }
+                    self.atlas.insert(
+                        handle,
+                        AtlasSlot {
+                            page,
+                            rect: entry.rect,
+                        },
+                    );
+                }
+                None => {
+                    if self.regions.contains_key(&entry.name) {
+                        let error = TextureError::DuplicateName(entry.name.clone());
+                        self.report(error.clone());
+                        return Err(error);
+                    }
+                    self.regions
+                        .insert(entry.name.clone(), Frame::with_source(page, entry.rect));
+                }
+            }
+        }
+
+        println!(
+            "TextureManager: add_atlas(): {} pages, {} entries",
+            pages.len(),
+            atlas.entries.len()
+        );
+        Ok(pages)
+    }
+
+    pub fn get_atlas_slot(&self, handle: Handle<Texture2D>) -> Option<AtlasSlot> {
+        self.atlas.get(&handle).copied()
+    }
+
+    /// Texture and source rect to draw the frame, with the atlas remapping applied.
+    pub fn resolve(&self, frame: &Frame) -> Option<(Texture2D, Option<Rect>)> {
+        let texture = *self.textures.get(frame.texture)?;
+        match self.atlas.get(&frame.texture) {
+            Some(slot) => Some((texture, Some(slot.map(frame.source)))),
+            None => Some((texture, frame.source)),
+        }
+    }
+
+    fn is_name_used(&self, name: &str) -> bool {
+        self.names.contains_key(name) || self.regions.contains_key(name)
+    }
+
+    /// Name a sub-rect of the sprite sheet. It can be used as an animation frame name.
+    pub fn add_region(
+        &mut self,
+        name: &str,
+        sheet: &str,
+        source: Rect,
+    ) -> Result<(), TextureError> {
+        let handle = match self.get_texture_handle(sheet) {
+            Some(v) => v,
+            None => {
+                let error = TextureError::UnknownName(sheet.to_owned());
+                self.report(error.clone());
+                return Err(error);
+            }
+        };
+
+        if self.is_name_used(name) {
+            let error = TextureError::DuplicateName(name.to_owned());
+            self.report(error.clone());
+            return Err(error);
+        }
+
+        let texture = self.textures.get(handle).copied();
+        if let Some(texture) = texture {
+            if source.x < 0.0
+                || source.y < 0.0
+                || source.w <= 0.0
+                || source.h <= 0.0
+                || source.right() > texture.width()
+                || source.bottom() > texture.height()
+            {
+                let error = TextureError::InvalidRegion {
+                    name: name.to_owned(),
+                    message: format!("{:?} is outside of {}", source, sheet),
+                };
+                self.report(error.clone());
+                return Err(error);
+            }
+        }
+
+        self.regions
+            .insert(name.to_owned(), Frame::with_source(handle, source));
+        Ok(())
+    }
+
+    /// Name every rect in the list. It stops at the first error.
+    pub fn slice_rects(
+        &mut self,
+        sheet: &str,
+        regions: &[(&str, Rect)],
+    ) -> Result<(), TextureError> {
+        for (name, source) in regions.iter() {
+            self.add_region(name, sheet, *source)?;
+        }
+        Ok(())
+    }
+
+    /// Cut the sprite sheet into cells named "{prefix}-0", "{prefix}-1", ... (left to right, top to bottom).
+    /// It return the names.
+    pub fn slice_grid(
+        &mut self,
+        sheet: &str,
+        prefix: &str,
+        grid: GridSlice,
+    ) -> Result<Vec<String>, TextureError> {
+        let texture = match self.get_texture(sheet) {
+            Some(v) => *v,
+            None => {
+                let error = TextureError::UnknownName(sheet.to_owned());
+                self.report(error.clone());
+                return Err(error);
+            }
+        };
+
+        if !grid.is_valid() {
+            let error = TextureError::InvalidRegion {
+                name: sheet.to_owned(),
+                message: format!(
+                    "{:?} needs a positive cell size and no negative margin or spacing",
+                    grid
+                ),
+            };
+            self.report(error.clone());
+            return Err(error);
+        }
+
+        let rects = grid.rects(texture.width(), texture.height());
+        if rects.is_empty() {
+            let error = TextureError::InvalidRegion {
+                name: sheet.to_owned(),
+                message: format!("{:?} has no cell", grid),
+            };
+            self.report(error.clone());
+            return Err(error);
+        }
+
+        let mut names = vec![];
+        for (index, source) in rects.iter().enumerate() {
+            let name = format!("{}-{}", prefix, index);
+            self.add_region(&name, sheet, *source)?;
+            names.push(name);
+        }
+        Ok(names)
+    }
+
+    /// Forget every region of the sprite sheet. It return the number of removed regions.
+    pub fn remove_regions(&mut self, sheet: &str) -> usize {
+        let handle = match self.get_texture_handle(sheet) {
+            Some(v) => v,
+            None => return 0,
+        };
+
+        let count = self.regions.len();
+        self.regions.retain(|_, frame| frame.texture != handle);
+        count - self.regions.len()
+    }
+
+    pub fn get_region(&self, name: &str) -> Option<Frame> {
+        self.regions.get(name).copied()
+    }
+
+    /// Region with this name, or the whole texture with this name.
+    pub fn get_frame(&self, name: &str) -> Option<Frame> {
+        match self.regions.get(name) {
+            Some(frame) => Some(*frame),
+            None => self.get_texture_handle(name).map(Frame::new),
+        }
+    }
+
+    pub fn unload(&mut self, name: &str) -> bool {
+        match self.get_texture_handle(name) {
+            Some(handle) => self.remove(handle),
+            None => false,
+        }
+    }
+
+    /// Tag the texture, so it can be unloaded with other textures in the same group (e.g. "level-2").
+    pub fn set_group(&mut self, name: &str, group: &str) -> bool {
+        let handle = match self.get_texture_handle(name) {
+            Some(v) => v,
+            None => return false,
+        };
+        match self.infos.get_mut(&handle) {
+            Some(info) => {
+                info.group = Some(group.to_owned());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Unload every texture in the group. It return the number of unloaded textures.
+    pub fn unload_group(&mut self, group: &str) -> usize {
+        let handles: Vec<Handle<Texture2D>> = self
+            .infos
+            .iter()
+            .filter(|(_, info)| info.group.as_deref() == Some(group))
+            .map(|(handle, _)| *handle)
+            .collect();
+
+        handles.iter().filter(|v| self.remove(**v)).count()
+    }
+
+    /// Get a strong reference to the texture. After the first call, the texture is reference counted
+    /// and `release_unused()` can free it when every `TextureRef` is dropped.
+    pub fn acquire(&mut self, handle: Handle<Texture2D>) -> Option<TextureRef> {
+        let info = self.infos.get_mut(&handle)?;
+        info.ref_counted = true;
+
+        Some(TextureRef {
+            handle,
+            _count: info.count.clone(),
+        })
+    }
+
+    /// Number of `TextureRef` (Sprite/Animation) that use the texture.
+    pub fn ref_count(&self, handle: Handle<Texture2D>) -> usize {
+        match self.infos.get(&handle) {
+            Some(info) => Arc::strong_count(&info.count) - 1,
+            None => 0,
+        }
+    }
+
+    /// Free reference counted textures that no `Sprite`/`Animation` use anymore.
+    /// `SceneManager` call it after a scene leaves the stack.
+    pub fn release_unused(&mut self) -> usize {
+        let unused = |info: &TextureInfo| info.ref_counted && Arc::strong_count(&info.count) == 1;
+        let handles: Vec<Handle<Texture2D>> = self
+            .infos
+            .iter()
+            .filter(|(_, info)| unused(info))
+            // Removing a page removes the textures packed into it, keep it while one of them is used.
+            .filter(|(handle, _)| {
+                self.atlas
+                    .iter()
+                    .filter(|(_, slot)| slot.page == **handle)
+                    .all(|(packed, _)| self.infos.get(packed).is_none_or(unused))
+            })
+            .map(|(handle, _)| *handle)
+            .collect();
+
+        let count = handles.iter().filter(|v| self.remove(**v)).count();
+        if count > 0 {
+            println!("TextureManager: release_unused(): {}", count);
+        }
+        count
+    }
+
+    /// Bytes used by the textures in the group. `None` is the textures without group.
+    pub fn memory_usage(&self, group: Option<&str>) -> usize {
+        self.infos
+            .values()
+            .filter(|info| info.group.as_deref() == group)
+            .map(|info| info.size)
+            .sum()
+    }
+
+    /// Bytes used by every group. Textures without group are under "".
+    pub fn memory_usage_by_group(&self) -> HashMap<String, usize> {
+        let mut usage = HashMap::new();
+        for info in self.infos.values() {
+            let group = info.group.clone().unwrap_or_default();
+            *usage.entry(group).or_insert(0) += info.size;
+        }
+        usage
+    }
+
+    /// Print and keep the error. `App` check them after loading assets when strict mode is on.
+    pub fn report(&mut self, error: TextureError) {
+        println!("TextureManager: {}", error);
+        self.errors.push(error);
+    }
+
+    pub fn errors(&self) -> &Vec<TextureError> {
+        &self.errors
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// The atlas page for a packed texture. Use `resolve()` to get the rect on it.
+    pub fn get_texture_by_handle(&self, handle: Handle<Texture2D>) -> Option<&Texture2D> {
+        self.textures.get(handle)
+    }
+
+    pub fn get_texture(&self, name: &str) -> Option<&Texture2D> {
+        match self.names.get(name) {
+            Some(handle) => self.textures.get(*handle),
+            None => None,
+        }
+    }
+
+    /// Built-in checkerboard texture. See `placeholder_image()`.
+    pub fn placeholder(&mut self) -> Texture2D {
+        *self.placeholder.get_or_insert_with(|| {
+            let texture = Texture2D::from_image(&placeholder_image());
+            texture.set_filter(FilterMode::Nearest);
+            texture
+        })
+    }
+
+    // Print once per missing asset, so the log is not flooded every frame.
+    fn warn_missing(&mut self, what: String) {
+        if self.missing.insert(what.clone()) {
+            println!(
+                "TextureManager: missing texture: {}: draw placeholder",
+                what
+            );
+        }
+    }
+
+    /// Same as `get_texture()`, but an unknown name gives the placeholder.
+    pub fn get_texture_or_placeholder(&mut self, name: &str) -> Texture2D {
+        match self.get_texture(name) {
+            Some(texture) => *texture,
+            None => {
+                self.warn_missing(name.to_owned());
+                self.placeholder()
+            }
+        }
+    }
+
+    /// Same as `resolve()`, but a stale handle gives the whole placeholder.
+    pub fn resolve_or_placeholder(&mut self, frame: &Frame) -> (Texture2D, Option<Rect>) {
+        match self.resolve(frame) {
+            Some(v) => v,
+            None => {
+                self.warn_missing(format!("{:?}", frame.texture));
+                (self.placeholder(), None)
+            }
+        }
+    }
+
+    pub fn get_texture_handle(&self, name: &str) -> Option<Handle<Texture2D>> {
+        self.names.get(name).copied()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.names.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Page with "hero" packed into it. `Texture2D::empty()` doesn't need a window.
+    fn packed_manager() -> (TextureManager, Handle<Texture2D>, Handle<Texture2D>) {
+        let mut manager = TextureManager::new();
+        let page = manager.add("atlas-0", Texture2D::empty()).unwrap();
+        let hero = manager.add("hero", Texture2D::empty()).unwrap();
+        manager.atlas.insert(
+            hero,
+            AtlasSlot {
+                page,
+                rect: Rect::new(0.0, 0.0, 16.0, 16.0),
+            },
+        );
+        (manager, page, hero)
+    }
+
+    #[test]
+    fn page_stays_while_a_packed_texture_is_used() {
+        let (mut manager, page, hero) = packed_manager();
+        drop(manager.acquire(page));
+        let hero_ref = manager.acquire(hero);
+
+        assert_eq!(manager.release_unused(), 0);
+        assert!(manager.get_texture_by_handle(page).is_some());
+        assert!(manager.get_texture_by_handle(hero).is_some());
+        assert_eq!(manager.get_atlas_slot(hero).map(|v| v.page), Some(page));
+        drop(hero_ref);
+    }
+
+    #[test]
+    fn page_stays_while_a_packed_texture_is_not_ref_counted() {
+        let (mut manager, page, hero) = packed_manager();
+        drop(manager.acquire(page));
+
+        assert_eq!(manager.release_unused(), 0);
+        assert!(manager.get_texture_by_handle(page).is_some());
+        assert!(manager.get_texture_by_handle(hero).is_some());
+    }
+
+    #[test]
+    fn unused_packed_texture_leaves_the_page() {
+        let (mut manager, page, hero) = packed_manager();
+        let page_ref = manager.acquire(page);
+        drop(manager.acquire(hero));
+
+        // The page is shared, removing "hero" doesn't delete it.
+        assert_eq!(manager.release_unused(), 1);
+        assert!(manager.get_texture_by_handle(hero).is_none());
+        assert!(manager.get_atlas_slot(hero).is_none());
+        assert!(manager.get_texture_by_handle(page).is_some());
+        drop(page_ref);
+    }
+}
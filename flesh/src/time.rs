@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+/// Timing of the current fixed update. `App` pass it into `GameScene::update` and it should be
+/// passed down to `Sprite::update` and game objects.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameTime {
+    // Length of one fixed update
+    pub delta: Duration,
+    // Total time of all fixed updates, including this one
+    pub elapsed: Duration,
+    // Number of fixed updates, including this one
+    pub frame: u64,
+}
+
+impl FrameTime {
+    pub fn new(delta: Duration) -> Self {
+        Self {
+            delta,
+            elapsed: Duration::ZERO,
+            frame: 0,
+        }
+    }
+
+    /// Move to the next fixed update.
+    pub fn advance(&mut self) {
+        self.elapsed += self.delta;
+        self.frame += 1;
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+}
@@ -0,0 +1,132 @@
+use macroquad::prelude::*;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug)]
+pub enum TransitionDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Visual effect that play while `SceneManager` is changing from the old scene to the new scene.
+#[derive(Clone, Copy, Debug)]
+pub enum TransitionEffect {
+    // Fade the old scene out to the color, then fade the new scene in from the same color
+    FadeToColor(Color),
+    // Blend the new scene over the old scene
+    Crossfade,
+    // Reveal the new scene with an edge moving toward the direction
+    Wipe(TransitionDirection),
+    // Push the old scene out of the screen toward the direction
+    Slide(TransitionDirection),
+}
+
+/// Effect and how long it takes. The duration is counted in fixed updates (`FrameTime::delta`).
+#[derive(Clone, Copy, Debug)]
+pub struct Transition {
+    pub effect: TransitionEffect,
+    pub duration: Duration,
+}
+
+impl Transition {
+    pub fn new(effect: TransitionEffect, duration: Duration) -> Self {
+        Self { effect, duration }
+    }
+
+    pub fn fade(color: Color, duration: Duration) -> Self {
+        Self::new(TransitionEffect::FadeToColor(color), duration)
+    }
+
+    pub fn crossfade(duration: Duration) -> Self {
+        Self::new(TransitionEffect::Crossfade, duration)
+    }
+
+    pub fn wipe(direction: TransitionDirection, duration: Duration) -> Self {
+        Self::new(TransitionEffect::Wipe(direction), duration)
+    }
+
+    pub fn slide(direction: TransitionDirection, duration: Duration) -> Self {
+        Self::new(TransitionEffect::Slide(direction), duration)
+    }
+
+    /// Draw both scenes (already rendered on `from` and `to` textures) at `progress` (0.0 - 1.0).
+    pub fn draw(&self, progress: f32, from: Texture2D, to: Texture2D, width: f32, height: f32) {
+        let progress = progress.clamp(0.0, 1.0);
+        match self.effect {
+            TransitionEffect::FadeToColor(color) => {
+                let (texture, alpha) = if progress < 0.5 {
+                    (from, progress * 2.0)
+                } else {
+                    (to, (1.0 - progress) * 2.0)
+                };
+                draw_texture(texture, 0.0, 0.0, WHITE);
+                draw_rectangle(
+                    0.0,
+                    0.0,
+                    width,
+                    height,
+                    Color::new(color.r, color.g, color.b, alpha),
+                );
+            }
+            TransitionEffect::Crossfade => {
+                draw_texture(from, 0.0, 0.0, WHITE);
+                draw_texture(to, 0.0, 0.0, Color::new(1.0, 1.0, 1.0, progress));
+            }
+            TransitionEffect::Wipe(direction) => {
+                draw_texture(from, 0.0, 0.0, WHITE);
+
+                let source = match direction {
+                    TransitionDirection::Right => Rect::new(0.0, 0.0, width * progress, height),
+                    TransitionDirection::Left => Rect::new(
+                        width * (1.0 - progress),
+                        0.0,
+                        width * progress,
+                        height,
+                    ),
+                    TransitionDirection::Down => Rect::new(0.0, 0.0, width, height * progress),
+                    TransitionDirection::Up => Rect::new(
+                        0.0,
+                        height * (1.0 - progress),
+                        width,
+                        height * progress,
+                    ),
+                };
+
+                draw_texture_ex(
+                    to,
+                    source.x,
+                    source.y,
+                    WHITE,
+                    DrawTextureParams {
+                        source: Some(source),
+                        ..Default::default()
+                    },
+                );
+            }
+            TransitionEffect::Slide(direction) => {
+                let (offset, to_offset) = match direction {
+                    TransitionDirection::Right => {
+                        let x = width * progress;
+                        (vec2(x, 0.0), vec2(x - width, 0.0))
+                    }
+                    TransitionDirection::Left => {
+                        let x = -width * progress;
+                        (vec2(x, 0.0), vec2(x + width, 0.0))
+                    }
+                    TransitionDirection::Down => {
+                        let y = height * progress;
+                        (vec2(0.0, y), vec2(0.0, y - height))
+                    }
+                    TransitionDirection::Up => {
+                        let y = -height * progress;
+                        (vec2(0.0, y), vec2(0.0, y + height))
+                    }
+                };
+
+                draw_texture(from, offset.x, offset.y, WHITE);
+                draw_texture(to, to_offset.x, to_offset.y, WHITE);
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@ pub fn draw_text_center(message: &str, font_size: u16, pos_y: f32) {
     let measure = measure_text(message, None, font_size, 1.0);
     draw_text(
         message,
-        (crate::GAME_WIDTH - measure.width) / 2.0,
+        (crate::app::virtual_size().x - measure.width) / 2.0,
         pos_y - 10.0 - measure.height,
         font_size as f32,
         BLACK,
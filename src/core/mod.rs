@@ -1,6 +0,0 @@
-pub mod animation;
-pub mod scene;
-pub mod screen_scaler;
-pub mod sprite;
-pub mod textures;
-pub mod utils;